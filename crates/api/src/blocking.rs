@@ -0,0 +1,84 @@
+#![cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+//! A synchronous client, for programs that don't run an async runtime of their own.
+//!
+//! [`Client`] has a blocking counterpart of every endpoint method on [`crate::Client`],
+//! returning the same types from [`generated_types`](crate::core::generated_types). Requests
+//! are driven on a small runtime owned by the client, so callers never need to set one up.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), ptv::Error> {
+//! let client = ptv::blocking::Client::new("DEVID".into(), "KEY".into())?;
+//! let directions = client.get_directions_by_route_id(ptv::RouteId(1))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Methods block the calling thread, so they must not be called from within an async runtime.
+
+use std::{future::Future, sync::Arc};
+
+use tokio::runtime::Runtime;
+
+use crate::{Cache, ClientBuilder, Error};
+
+#[derive(Clone)]
+pub struct Client {
+    pub(crate) inner: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    pub fn new(devid: String, token: String) -> Result<Self, Error> {
+        Self::from_async(crate::Client::new(devid, token))
+    }
+
+    /// Wrap an already configured async [`Client`](crate::Client).
+    pub fn from_async(inner: crate::Client) -> Result<Self, Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| Error::Transport(e.into()))?;
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// A client using the credentials in the environment, see [`Credentials::from_env`](crate::Credentials::from_env).
+    pub fn from_env() -> Result<Self, Error> {
+        ClientBuilder::from_env()?.build_blocking()
+    }
+
+    /// A client configured from a TOML file, see [`ClientBuilder::from_config_file`].
+    pub fn from_config_file(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        ClientBuilder::from_config_file(path)?.build_blocking()
+    }
+
+    /// Configure a client's base URL, timeouts, headers, proxy and retries,
+    /// then finish with [`ClientBuilder::build_blocking`].
+    pub fn builder(devid: impl Into<String>, token: impl Into<String>) -> ClientBuilder {
+        ClientBuilder::new(devid, token)
+    }
+
+    /// The async client requests are sent through.
+    pub fn as_async(&self) -> &crate::Client {
+        &self.inner
+    }
+
+    /// The response cache, for explicit invalidation. See [`ClientBuilder::cache`].
+    pub fn cache(&self) -> Option<&Cache> {
+        self.inner.cache()
+    }
+
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
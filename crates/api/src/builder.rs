@@ -0,0 +1,290 @@
+#![cfg(not(target_arch = "wasm32"))]
+use std::{path::Path, sync::Arc, time::Duration};
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+
+use crate::{
+    API_URL, Cache, CircuitBreaker, CircuitBreakerPolicy, Client, Credentials, Error, KeyPool,
+    Limiter, Middleware, RateLimit, ReqwestTransport, RetryPolicy, Transport,
+};
+
+/// Configures and builds a [`Client`].
+///
+/// Every client built here owns a single HTTP client (and its connection pool),
+/// which is reused by all generated methods and shared by clones of the [`Client`].
+///
+/// ```no_run
+/// # fn main() -> Result<(), ptv::Error> {
+/// let client = ptv::Client::builder("DEVID", "KEY")
+///     .timeout(std::time::Duration::from_secs(10))
+///     .user_agent("departure-board/1.0")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ClientBuilder {
+    credentials: Credentials,
+    base_url: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    default_headers: HeaderMap,
+    proxy: Option<reqwest::Proxy>,
+    transport: Option<Arc<dyn Transport>>,
+    retry: Option<RetryPolicy>,
+    cache: Option<Cache>,
+    keys: Option<Arc<KeyPool>>,
+    rate_limit: Option<RateLimit>,
+    circuit_breaker: Option<CircuitBreakerPolicy>,
+    coalesce: bool,
+    middleware: Vec<Arc<dyn Middleware>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::Metrics>,
+    lenient: bool,
+}
+
+impl ClientBuilder {
+    pub fn new(devid: impl Into<String>, token: impl Into<String>) -> Self {
+        Self::with_credentials(Credentials::new(devid, token))
+    }
+
+    pub fn with_credentials(credentials: Credentials) -> Self {
+        Self {
+            credentials,
+            base_url: API_URL.to_string(),
+            timeout: None,
+            connect_timeout: None,
+            user_agent: None,
+            default_headers: HeaderMap::new(),
+            proxy: None,
+            transport: None,
+            retry: None,
+            cache: None,
+            keys: None,
+            rate_limit: None,
+            circuit_breaker: None,
+            coalesce: false,
+            middleware: Vec::new(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+            lenient: false,
+        }
+    }
+
+    /// Start from the credentials in the environment, see [`Credentials::from_env`].
+    pub fn from_env() -> Result<Self, Error> {
+        Ok(Self::with_credentials(Credentials::from_env()?))
+    }
+
+    /// Start from a TOML configuration file:
+    ///
+    /// ```toml
+    /// devid = "1000000"
+    /// key = "00000000-0000-0000-0000-000000000000"
+    /// # Optional
+    /// base_url = "http://localhost:8080"
+    /// timeout_secs = 10
+    /// user_agent = "departure-board/1.0"
+    /// ```
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("Could not read {}: {e}", path.display())))?;
+        let config: ConfigFile = toml::from_str(&contents)
+            .map_err(|e| Error::Config(format!("Could not parse {}: {e}", path.display())))?;
+
+        let mut builder = Self::with_credentials(config.credentials);
+        if let Some(base_url) = config.base_url {
+            builder = builder.base_url(base_url);
+        }
+        if let Some(timeout) = config.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        if let Some(user_agent) = config.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(builder)
+    }
+
+    /// Base URL requests are sent to, defaults to [`API_URL`].
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Total time allowed for each request, from connecting until the body has been read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Time allowed for establishing a connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Add a header sent with every request.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers.extend(headers);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Send requests through `transport` instead of building a [`ReqwestTransport`].
+    ///
+    /// The HTTP options on this builder (timeouts, user agent, headers and proxy)
+    /// only apply to the default transport and are ignored when this is set.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Retry transient failures according to `policy`. Requests are not retried by default.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Serve repeated requests from `cache` while its [`CachePolicy`](crate::CachePolicy) allows.
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Sign requests with keys from `pool` instead of the builder's own credentials.
+    pub fn key_pool(mut self, pool: KeyPool) -> Self {
+        self.keys = Some(Arc::new(pool));
+        self
+    }
+
+    /// Limit how many requests are in flight and how fast they are sent. See [`RateLimit`].
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Fail fast while the API is degraded. See [`CircuitBreaker`](crate::CircuitBreaker).
+    pub fn circuit_breaker(mut self, policy: CircuitBreakerPolicy) -> Self {
+        self.circuit_breaker = Some(policy);
+        self
+    }
+
+    /// Let identical concurrent calls share a single request: a call whose path matches a
+    /// request already in flight waits for its response instead of sending its own.
+    pub fn coalesce(mut self, coalesce: bool) -> Self {
+        self.coalesce = coalesce;
+        self
+    }
+
+    /// Run every request through `middleware`, inside any added before it.
+    /// See [`middleware`](crate::middleware).
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Record per-endpoint call counts, errors, latency and response sizes in `metrics`.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, metrics: crate::Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Drop values that don't match the spec instead of failing the whole response. Dropped
+    /// values are reported in [`ApiResponse::warnings`](crate::ApiResponse::warnings), see
+    /// [`lenient`](crate::lenient).
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    pub fn build(mut self) -> Result<Client, Error> {
+        if self.keys.as_ref().is_some_and(|keys| keys.is_empty()) {
+            return Err(Error::Config("Key pool is empty".to_string()));
+        }
+        if let Some(policy) = &self.retry {
+            policy.validate()?;
+        }
+        if let Some(limit) = &self.rate_limit {
+            limit.validate()?;
+        }
+        if let Some(policy) = &self.circuit_breaker {
+            policy.validate()?;
+        }
+        let transport = match self.transport.take() {
+            Some(transport) => transport,
+            None => {
+                let mut builder = reqwest::Client::builder()
+                    .default_headers(std::mem::take(&mut self.default_headers));
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(user_agent) = self.user_agent.take() {
+                    builder = builder.user_agent(user_agent);
+                }
+                if let Some(proxy) = self.proxy.take() {
+                    builder = builder.proxy(proxy);
+                }
+                let client = builder.build().map_err(|e| Error::Transport(e.into()))?;
+                Arc::new(ReqwestTransport::new(client))
+            }
+        };
+
+        Ok(self.finish(transport))
+    }
+
+    /// Build a [`blocking::Client`](crate::blocking::Client) instead of an async one.
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> Result<crate::blocking::Client, Error> {
+        crate::blocking::Client::from_async(self.build()?)
+    }
+
+    pub(crate) fn finish(self, transport: Arc<dyn Transport>) -> Client {
+        Client {
+            credentials: self.credentials,
+            base_url: self.base_url,
+            transport,
+            retry: self.retry,
+            cache: self.cache,
+            keys: self.keys,
+            limiter: self.rate_limit.map(|limit| Arc::new(Limiter::new(limit))),
+            breaker: self
+                .circuit_breaker
+                .map(|policy| Arc::new(CircuitBreaker::new(policy))),
+            coalescer: self.coalesce.then(Default::default),
+            middleware: self.middleware,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+            lenient: self.lenient,
+        }
+    }
+}
+
+/// The file read by [`ClientBuilder::from_config_file`].
+#[derive(Deserialize)]
+struct ConfigFile {
+    #[serde(flatten)]
+    credentials: Credentials,
+    base_url: Option<String>,
+    timeout_secs: Option<u64>,
+    user_agent: Option<String>,
+}
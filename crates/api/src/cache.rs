@@ -0,0 +1,349 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Response caching for [`Client`](crate::Client).
+//!
+//! Raw response bodies are cached below the generated methods, keyed by the unsigned
+//! request path (path and query, without `devid` or `signature`). How long a response
+//! lives is decided per endpoint family by a [`CachePolicy`].
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+/// Storage for cached response bodies. Implementations are responsible for expiry.
+pub trait CacheBackend: Send + Sync {
+    fn get(&self, key: &str) -> Option<String>;
+    fn put(&self, key: &str, body: &str, ttl: Duration);
+    fn invalidate(&self, key: &str);
+    /// Drop every entry whose key starts with `prefix`.
+    fn invalidate_prefix(&self, prefix: &str);
+    fn clear(&self);
+}
+
+/// Time-to-live rules, matched against the request path by longest prefix.
+///
+/// A prefix only matches on a path segment boundary, so `/v3/routes` covers
+/// `/v3/routes/1` and `/v3/routes?route_types=0` but not `/v3/route_types`.
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    rules: Vec<(String, Duration)>,
+    default: Option<Duration>,
+}
+
+impl Default for CachePolicy {
+    /// Long-lived timetable data (route types, routes, stops, directions) is cached for
+    /// hours, departures for a few seconds. Anything else is not cached.
+    fn default() -> Self {
+        Self::empty()
+            .ttl("/v3/route_types", Duration::from_secs(24 * 60 * 60))
+            .ttl("/v3/routes", Duration::from_secs(60 * 60))
+            .ttl("/v3/stops", Duration::from_secs(60 * 60))
+            .ttl("/v3/directions", Duration::from_secs(60 * 60))
+            .ttl("/v3/departures", Duration::from_secs(30))
+    }
+}
+
+impl CachePolicy {
+    /// A policy that caches nothing until rules are added.
+    pub fn empty() -> Self {
+        Self {
+            rules: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Cache responses for paths under `prefix` for `ttl`. A zero `ttl` disables caching for them.
+    pub fn ttl(mut self, prefix: impl Into<String>, ttl: Duration) -> Self {
+        let prefix = prefix.into();
+        self.rules.retain(|(p, _)| *p != prefix);
+        self.rules.push((prefix, ttl));
+        self
+    }
+
+    /// TTL for paths that match no rule.
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        self.default = Some(ttl);
+        self
+    }
+
+    /// How long the response to `path` may be cached, if at all.
+    pub fn ttl_for(&self, path: &str) -> Option<Duration> {
+        self.rules
+            .iter()
+            .filter(|(prefix, _)| matches_prefix(path, prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, ttl)| *ttl)
+            .or(self.default)
+            .filter(|ttl| !ttl.is_zero())
+    }
+}
+
+fn matches_prefix(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix).is_some_and(|rest| {
+        rest.is_empty() || rest.starts_with(['/', '?']) || prefix.ends_with('/')
+    })
+}
+
+/// A [`CacheBackend`] together with the [`CachePolicy`] deciding what goes in it.
+#[derive(Clone)]
+pub struct Cache {
+    backend: Arc<dyn CacheBackend>,
+    policy: CachePolicy,
+}
+
+impl Cache {
+    pub fn new(backend: impl CacheBackend + 'static) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            policy: CachePolicy::default(),
+        }
+    }
+
+    /// An in-memory LRU cache holding at most `capacity` responses.
+    pub fn memory(capacity: usize) -> Self {
+        Self::new(MemoryCache::new(capacity))
+    }
+
+    /// A cache persisted as files in `dir`.
+    pub fn disk(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        Ok(Self::new(DiskCache::new(dir)?))
+    }
+
+    pub fn policy(mut self, policy: CachePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn get(&self, path: &str) -> Option<String> {
+        self.policy.ttl_for(path)?;
+        self.backend.get(path)
+    }
+
+    pub fn put(&self, path: &str, body: &str) {
+        if let Some(ttl) = self.policy.ttl_for(path) {
+            self.backend.put(path, body, ttl);
+        }
+    }
+
+    pub fn invalidate(&self, path: &str) {
+        self.backend.invalidate(path);
+    }
+
+    /// Drop every cached response for paths starting with `prefix`, e.g. `/v3/routes`.
+    pub fn invalidate_prefix(&self, prefix: &str) {
+        self.backend.invalidate_prefix(prefix);
+    }
+
+    pub fn clear(&self) {
+        self.backend.clear();
+    }
+}
+
+struct MemoryEntry {
+    body: String,
+    /// `None` when the TTL is too long to represent, i.e. never.
+    expires: Option<Instant>,
+    tick: u64,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    entries: HashMap<String, MemoryEntry>,
+    /// Keys ordered by last use, oldest first.
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl MemoryState {
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.tick);
+        }
+    }
+}
+
+/// An in-memory, least-recently-used [`CacheBackend`].
+pub struct MemoryCache {
+    capacity: usize,
+    state: Mutex<MemoryState>,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheBackend for MemoryCache {
+    fn get(&self, key: &str) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let entry = state.entries.get_mut(key)?;
+        if entry
+            .expires
+            .is_some_and(|expires| expires <= Instant::now())
+        {
+            state.remove(key);
+            return None;
+        }
+        state.tick += 1;
+        state.order.remove(&entry.tick);
+        entry.tick = state.tick;
+        state.order.insert(entry.tick, key.to_string());
+        Some(entry.body.clone())
+    }
+
+    fn put(&self, key: &str, body: &str, ttl: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.remove(key);
+        while state.entries.len() >= self.capacity {
+            let Some((_, oldest)) = state.order.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state.order.insert(tick, key.to_string());
+        state.entries.insert(
+            key.to_string(),
+            MemoryEntry {
+                body: body.to_string(),
+                expires: Instant::now().checked_add(ttl),
+                tick,
+            },
+        );
+    }
+
+    fn invalidate(&self, key: &str) {
+        self.state.lock().unwrap().remove(key);
+    }
+
+    fn invalidate_prefix(&self, prefix: &str) {
+        let mut state = self.state.lock().unwrap();
+        let keys = state
+            .entries
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in keys {
+            state.remove(&key);
+        }
+    }
+
+    fn clear(&self) {
+        *self.state.lock().unwrap() = MemoryState::default();
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    key: String,
+    /// Seconds since the unix epoch.
+    expires: u64,
+    body: String,
+}
+
+/// A [`CacheBackend`] storing one JSON file per response in a directory,
+/// so cached data survives restarts.
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    pub fn new(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn file(&self, key: &str) -> PathBuf {
+        let hash = hex::encode(Sha1::digest(key.as_bytes()));
+        self.dir.join(format!("{hash}.json"))
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (PathBuf, DiskEntry)> {
+        std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let entry = serde_json::from_slice(&std::fs::read(&path).ok()?).ok()?;
+                Some((path, entry))
+            })
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl CacheBackend for DiskCache {
+    fn get(&self, key: &str) -> Option<String> {
+        let file = self.file(key);
+        let entry: DiskEntry = serde_json::from_slice(&std::fs::read(&file).ok()?).ok()?;
+        if entry.key != key {
+            return None;
+        }
+        if entry.expires <= now() {
+            std::fs::remove_file(file).ok();
+            return None;
+        }
+        Some(entry.body)
+    }
+
+    fn put(&self, key: &str, body: &str, ttl: Duration) {
+        let entry = DiskEntry {
+            key: key.to_string(),
+            // Saturates, so a TTL too long to represent never expires.
+            expires: now().saturating_add(ttl.as_secs().max(1)),
+            body: body.to_string(),
+        };
+        if let Ok(json) = serde_json::to_vec(&entry) {
+            std::fs::write(self.file(key), json).ok();
+        }
+    }
+
+    fn invalidate(&self, key: &str) {
+        std::fs::remove_file(self.file(key)).ok();
+    }
+
+    fn invalidate_prefix(&self, prefix: &str) {
+        for (path, entry) in self.entries() {
+            if entry.key.starts_with(prefix) {
+                std::fs::remove_file(path).ok();
+            }
+        }
+    }
+
+    fn clear(&self) {
+        for (path, _) in self.entries() {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
@@ -0,0 +1,233 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Record-and-replay fixtures for offline testing.
+//!
+//! A [`Cassette`] is a [`Transport`] that, when recording, passes requests through to a real
+//! transport and writes every response to a fixture file; when replaying, it serves those
+//! fixtures and fails on any request it has no recording for.
+//!
+//! Fixtures are keyed by the method and the unsigned request path, plus any request headers
+//! and body: `devid` and `signature` are stripped before anything is written, so fixtures
+//! can be committed and replayed with any credentials.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+pub use crate::signing::unsigned_path;
+use crate::{BoxFuture, Request, Transport, TransportError, TransportResponse};
+
+/// Environment variable selecting the cassette mode in test harnesses, see [`CassetteMode::from_env`].
+pub const CASSETTE_ENV: &str = "PTV_CASSETTE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+impl CassetteMode {
+    /// Reads [`CASSETTE_ENV`]: `record` or `replay`, anything else means no cassette.
+    pub fn from_env() -> Option<Self> {
+        match std::env::var(CASSETTE_ENV).ok()?.to_lowercase().as_str() {
+            "record" => Some(CassetteMode::Record),
+            "replay" => Some(CassetteMode::Replay),
+            _ => None,
+        }
+    }
+}
+
+/// A single recorded response.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Fixture {
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// The unsigned request path, including its query string.
+    pub path: String,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// The raw response body, exactly as received.
+    pub body: String,
+}
+
+fn default_method() -> String {
+    Method::GET.to_string()
+}
+
+pub struct Cassette {
+    dir: PathBuf,
+    inner: Option<Arc<dyn Transport>>,
+}
+
+impl Cassette {
+    /// Send requests through `inner` and record every response into `dir`.
+    pub fn record(dir: impl Into<PathBuf>, inner: impl Transport + 'static) -> Self {
+        Self {
+            dir: dir.into(),
+            inner: Some(Arc::new(inner)),
+        }
+    }
+
+    /// Serve responses recorded in `dir`, failing on anything that wasn't recorded.
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            inner: None,
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        match self.inner {
+            Some(_) => CassetteMode::Record,
+            None => CassetteMode::Replay,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The fixture file used for a `GET` of `path`.
+    pub fn fixture_file(&self, path: &str) -> PathBuf {
+        self.request_file(&Request::new(path))
+    }
+
+    /// The fixture file used for `request`, whose path is unsigned.
+    pub fn request_file(&self, request: &Request) -> PathBuf {
+        let mut name = request
+            .path
+            .trim_start_matches('/')
+            .split(['?', '&'])
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        let mut hasher = Sha1::new();
+        hasher.update(request.path.as_bytes());
+        // A plain `GET` is keyed by its path alone, as it always has been.
+        if request.method != Method::GET {
+            name = format!("{}_{name}", request.method.as_str().to_lowercase());
+            hasher.update(format!("\n{}", request.method));
+        }
+        let mut headers = request
+            .headers
+            .iter()
+            .map(|(name, value)| format!("\n{name}: {}", String::from_utf8_lossy(value.as_bytes())))
+            .collect::<Vec<_>>();
+        headers.sort();
+        for header in headers {
+            hasher.update(header);
+        }
+        if let Some(body) = &request.body {
+            hasher.update(format!("\n\n{body}"));
+        }
+        let hash = hex::encode(hasher.finalize());
+        self.dir.join(format!("{name}-{}.json", &hash[..12]))
+    }
+
+    fn load(&self, request: &Request) -> Result<TransportResponse, TransportError> {
+        let (method, path) = (&request.method, &request.path);
+        let file = self.request_file(request);
+        let fixture: Fixture = match std::fs::read(&file) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) => {
+                return Err(format!(
+                    "Cassette has no recording for {method} {path} (expected {}: {e}); \
+                     record it with {CASSETTE_ENV}=record",
+                    file.display()
+                )
+                .into());
+            }
+        };
+        if fixture.method != method.as_str() || fixture.path != *path {
+            return Err(format!(
+                "Cassette fixture {} was recorded for {} {}, not {method} {path}",
+                file.display(),
+                fixture.method,
+                fixture.path
+            )
+            .into());
+        }
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in fixture.headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(&value)?,
+            );
+        }
+        Ok(TransportResponse {
+            status: StatusCode::from_u16(fixture.status)?,
+            headers,
+            body: fixture.body,
+        })
+    }
+
+    fn save(&self, request: &Request, res: &TransportResponse) -> Result<(), TransportError> {
+        let fixture = Fixture {
+            method: request.method.to_string(),
+            path: request.path.clone(),
+            status: res.status.as_u16(),
+            headers: res
+                .headers
+                .iter()
+                .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.into())))
+                .collect(),
+            body: res.body.clone(),
+        };
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(
+            self.request_file(request),
+            serde_json::to_vec_pretty(&fixture)?,
+        )?;
+        Ok(())
+    }
+
+    /// Record the response `send` gets from the inner transport for `request`, or replay it.
+    async fn exchange<'a>(
+        &'a self,
+        request: Request,
+        send: impl FnOnce(&'a dyn Transport) -> BoxFuture<'a, Result<TransportResponse, TransportError>>,
+    ) -> Result<TransportResponse, TransportError> {
+        match &self.inner {
+            Some(inner) => {
+                let res = send(inner.as_ref()).await?;
+                self.save(&request, &res)?;
+                Ok(res)
+            }
+            None => self.load(&request),
+        }
+    }
+}
+
+impl Transport for Cassette {
+    fn send<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        Box::pin(
+            self.exchange(Request::new(unsigned_path(url)), move |inner| {
+                inner.send(url)
+            }),
+        )
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        request: &'a Request,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        let unsigned = Request {
+            path: unsigned_path(url),
+            ..request.clone()
+        };
+        Box::pin(self.exchange(unsigned, move |inner| inner.send_request(request, url)))
+    }
+}
@@ -0,0 +1,266 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Failing fast while the API is degraded.
+//!
+//! A [`CircuitBreaker`] watches the outcome of recent requests. Once too many of them
+//! failed (no response, `429 Too Many Requests`, a `5xx` status, or a body reporting
+//! `status.health` of `0`), it opens and every request fails with [`Error::CircuitOpen`]
+//! without being sent. After [`probe_interval`](CircuitBreakerPolicy::probe_interval) the
+//! next request first probes the API with a cheap call; if that reports the API healthy
+//! the breaker closes again, otherwise it stays open for another interval.
+//!
+//! Responses served from the cache are unaffected.
+
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use http::StatusCode;
+use serde::Deserialize;
+
+use crate::{Error, Status, TransportResponse};
+
+/// Opt-in circuit breaking for [`Client`](crate::Client), set with
+/// [`ClientBuilder::circuit_breaker`](crate::ClientBuilder::circuit_breaker).
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerPolicy {
+    /// Number of most recent requests the failure rate is computed over.
+    pub window: usize,
+    /// Requests in the window before the breaker may open.
+    pub min_requests: usize,
+    /// Fraction (`0.0..=1.0`) of failed requests in the window that opens the breaker.
+    pub failure_rate: f64,
+    /// How long the breaker stays open before probing the API.
+    pub probe_interval: Duration,
+    /// The path requested to check whether the API has recovered.
+    pub probe_path: String,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        Self {
+            window: 20,
+            min_requests: 10,
+            failure_rate: 0.5,
+            probe_interval: Duration::from_secs(30),
+            probe_path: "/v3/route_types".to_string(),
+        }
+    }
+}
+
+impl CircuitBreakerPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn window(mut self, window: usize, min_requests: usize) -> Self {
+        self.window = window;
+        self.min_requests = min_requests;
+        self
+    }
+
+    pub fn failure_rate(mut self, rate: f64) -> Self {
+        self.failure_rate = rate;
+        self
+    }
+
+    pub fn probe_interval(mut self, interval: Duration) -> Self {
+        self.probe_interval = interval;
+        self
+    }
+
+    pub fn probe_path(mut self, path: impl Into<String>) -> Self {
+        self.probe_path = path.into();
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        if self.min_requests == 0 || self.min_requests > self.window {
+            return Err(Error::Config(format!(
+                "Circuit breaker needs 0 < min_requests <= window, got {} and {}",
+                self.min_requests, self.window
+            )));
+        }
+        if !(self.failure_rate > 0.0 && self.failure_rate <= 1.0) {
+            return Err(Error::Config(format!(
+                "Circuit breaker failure_rate must be in (0, 1], got {}",
+                self.failure_rate
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent.
+    Closed,
+    /// Requests fail fast until the next probe.
+    Open,
+    /// A probe is in flight; other requests fail fast.
+    HalfOpen,
+}
+
+struct Inner {
+    state: CircuitState,
+    /// Recent outcomes, `true` for a failure.
+    outcomes: VecDeque<bool>,
+    opened: Instant,
+}
+
+/// Tracks recent failures and decides whether requests may be sent.
+/// Shared by every clone of a [`Client`](crate::Client).
+pub struct CircuitBreaker {
+    policy: CircuitBreakerPolicy,
+    inner: Mutex<Inner>,
+}
+
+pub(crate) enum Gate<'a> {
+    Allow,
+    /// Probe the API, then [`Probe::finish`].
+    Probe(Probe<'a>),
+    Reject {
+        retry_in: Duration,
+    },
+}
+
+/// The right to probe the API. Dropping it unfinished reopens the breaker.
+pub(crate) struct Probe<'a> {
+    breaker: &'a CircuitBreaker,
+    finished: bool,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(policy: CircuitBreakerPolicy) -> Self {
+        Self {
+            policy,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                outcomes: VecDeque::new(),
+                opened: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn policy(&self) -> &CircuitBreakerPolicy {
+        &self.policy
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.inner.lock().unwrap().state
+    }
+
+    /// Close the breaker and forget recent failures.
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = CircuitState::Closed;
+        inner.outcomes.clear();
+    }
+
+    pub(crate) fn gate(&self) -> Gate<'_> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Closed => Gate::Allow,
+            CircuitState::Open => {
+                let elapsed = inner.opened.elapsed();
+                if elapsed < self.policy.probe_interval {
+                    return Gate::Reject {
+                        retry_in: self.policy.probe_interval - elapsed,
+                    };
+                }
+                inner.state = CircuitState::HalfOpen;
+                Gate::Probe(Probe {
+                    breaker: self,
+                    finished: false,
+                })
+            }
+            CircuitState::HalfOpen => Gate::Reject {
+                retry_in: Duration::ZERO,
+            },
+        }
+    }
+
+    /// Record the outcome of a request that was let through.
+    pub(crate) fn record(&self, failed: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != CircuitState::Closed {
+            return;
+        }
+        inner.outcomes.push_back(failed);
+        if inner.outcomes.len() > self.policy.window {
+            inner.outcomes.pop_front();
+        }
+        let failures = inner.outcomes.iter().filter(|failed| **failed).count();
+        let requests = inner.outcomes.len();
+        if requests >= self.policy.min_requests
+            && failures as f64 >= self.policy.failure_rate * requests as f64
+        {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(failures, requests, "circuit breaker opened");
+            self.open(&mut inner);
+        }
+    }
+
+    fn open(&self, inner: &mut Inner) {
+        inner.state = CircuitState::Open;
+        inner.opened = Instant::now();
+        inner.outcomes.clear();
+    }
+}
+
+impl Probe<'_> {
+    pub(crate) fn finish(mut self, healthy: bool) {
+        self.finished = true;
+        let mut inner = self.breaker.inner.lock().unwrap();
+        #[cfg(feature = "tracing")]
+        tracing::info!(healthy, "circuit breaker probed the API");
+        if healthy {
+            inner.state = CircuitState::Closed;
+        } else {
+            self.breaker.open(&mut inner);
+        }
+    }
+}
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let mut inner = self.breaker.inner.lock().unwrap();
+            self.breaker.open(&mut inner);
+        }
+    }
+}
+
+/// Whether `res` shows the API in trouble, or `None` if it says nothing about its health.
+pub(crate) fn failed(res: &Result<TransportResponse, Error>) -> Option<bool> {
+    match res {
+        Ok(res) => Some(!healthy(res)),
+        Err(e) => error_failed(e),
+    }
+}
+
+fn error_failed(e: &Error) -> Option<bool> {
+    match e {
+        Error::Transport(_) => Some(true),
+        Error::Status { status, .. } => {
+            Some(status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS)
+        }
+        Error::RetriesExhausted { source, .. } => error_failed(source),
+        _ => None,
+    }
+}
+
+/// Whether a successful response doesn't report the API as offline.
+pub(crate) fn healthy(res: &TransportResponse) -> bool {
+    #[derive(Deserialize)]
+    struct Health {
+        status: Option<Status>,
+    }
+    !matches!(
+        serde_json::from_str::<Health>(&res.body),
+        Ok(Health {
+            status: Some(Status { health: 0, .. })
+        })
+    )
+}
@@ -0,0 +1,88 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Sharing one request between identical concurrent calls.
+//!
+//! With [`ClientBuilder::coalesce`](crate::ClientBuilder::coalesce) on, a call whose unsigned
+//! path matches a request already in flight waits for that request instead of sending its
+//! own. The raw response is shared, so each caller still deserializes its own value. If
+//! the caller that started the request is cancelled, a waiting caller takes over.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::broadcast;
+
+use crate::{Error, TransportResponse};
+
+type Shared = Result<TransportResponse, Arc<Error>>;
+
+/// The requests currently in flight, by unsigned path.
+#[derive(Default)]
+pub(crate) struct Coalescer {
+    calls: Mutex<HashMap<String, broadcast::Sender<Shared>>>,
+}
+
+pub(crate) enum Flight<'a> {
+    /// No identical request is in flight; send it and [`Leader::finish`] with the result.
+    Leader(Leader<'a>),
+    /// Wait for the result of the identical request in flight.
+    Follower(broadcast::Receiver<Shared>),
+}
+
+/// The caller sending a request. Dropping it without finishing lets followers take over.
+pub(crate) struct Leader<'a> {
+    coalescer: &'a Coalescer,
+    path: String,
+    sender: broadcast::Sender<Shared>,
+}
+
+impl Coalescer {
+    pub(crate) fn join(&self, path: &str) -> Flight<'_> {
+        let mut calls = self.calls.lock().unwrap();
+        if let Some(sender) = calls.get(path) {
+            return Flight::Follower(sender.subscribe());
+        }
+        let (sender, _) = broadcast::channel(1);
+        calls.insert(path.to_string(), sender.clone());
+        Flight::Leader(Leader {
+            coalescer: self,
+            path: path.to_string(),
+            sender,
+        })
+    }
+}
+
+impl Leader<'_> {
+    /// Hand the result to every follower and return it.
+    pub(crate) fn finish(
+        self,
+        res: Result<TransportResponse, Error>,
+    ) -> Result<TransportResponse, Error> {
+        let shared = match &res {
+            Ok(res) => Ok(res.clone()),
+            Err(e) => Err(Arc::new(e.duplicate())),
+        };
+        self.coalescer.calls.lock().unwrap().remove(&self.path);
+        // Fails only when there are no followers.
+        self.sender.send(shared).ok();
+        res
+    }
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        let mut calls = self.coalescer.calls.lock().unwrap();
+        if calls
+            .get(&self.path)
+            .is_some_and(|sender| sender.same_channel(&self.sender))
+        {
+            calls.remove(&self.path);
+        }
+    }
+}
+
+/// A shared result as the follower's own.
+pub(crate) fn unshare(res: Shared) -> Result<TransportResponse, Error> {
+    res.map_err(|e| e.duplicate())
+}
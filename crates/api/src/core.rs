@@ -1,13 +1,8 @@
-#![cfg(not(target_arch = "wasm32"))]
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Duration};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{sync::Arc, time::Instant};
 
-use {
-    crate::*,
-    anyhow::Result,
-    hmac::{Hmac, Mac},
-    serde::de::DeserializeOwned,
-    sha1::Sha1,
-};
+use {crate::*, serde::de::DeserializeOwned};
 
 use serde::{Deserialize, Serialize};
 
@@ -15,10 +10,6 @@ use code_generator::SwaggerClient;
 
 pub const API_URL: &str = "https://timetableapi.ptv.vic.gov.au";
 
-type PtvHmac = Hmac<Sha1>;
-
-use anyhow::Error;
-
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Modes(#[serde(serialize_with = "ser_disruption_query")] pub DisruptionMode);
 
@@ -35,66 +26,481 @@ pub enum DateTime {
     strip_prefix = "V3.",
     extra_names = [("RouteType", "crate::ty::RouteType"), ("Status", "crate::ty::Status"), ("Expand", "Vec<crate::ty::ExpandOptions>"), ("ServiceOperator", "crate::ty::ServiceOperator"), ("DisruptionStatus", "crate::ty::DisruptionStatus"), ("Geopath", "Option<crate::ty::Geopath>"),("RouteId", "crate::ty::RouteId"),("StopId", "crate::ty::StopId"),("RunId", "crate::ty::RunId"),("DirectionId", "crate::ty::DirectionId"),("DisruptionId", "crate::ty::DisruptionId"), ("DisruptionMode", "crate::ty::DisruptionMode"), ("DisruptionModes", "crate::core::Modes"), ("DateTime", "crate::core::DateTime")],
     path_skip = ["/v3/disruptions/modes", "/v3/routes/types"],
-    skip = ["signature"]
+    skip = ["devid", "token", "signature"],
+    error = "crate::error::Error",
+    blocking = "crate::blocking::Client",
+    blocking_cfg = "all(feature = \"blocking\", not(target_arch = \"wasm32\"))",
+    response = "crate::ApiResponse",
+    options = "crate::CallOptions",
+    options_cfg = "not(target_arch = \"wasm32\")",
+    instrument = "tracing::instrument",
+    instrument_cfg = "feature = \"tracing\"",
+    named = true,
+    extras = true
 )]
+#[derive(Clone)]
 pub struct Client {
-    #[swagger(static)]
-    devid: String,
-    #[swagger(static)]
-    token: String,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) credentials: Credentials,
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) signer: Signer,
+    pub(crate) base_url: String,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) transport: Arc<dyn Transport>,
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) transport: FetchTransport,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) retry: Option<RetryPolicy>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) cache: Option<Cache>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) keys: Option<Arc<KeyPool>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) limiter: Option<Arc<Limiter>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) breaker: Option<Arc<CircuitBreaker>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) coalescer: Option<Arc<coalesce::Coalescer>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) middleware: Vec<Arc<dyn Middleware>>,
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    pub(crate) metrics: Option<Metrics>,
+    pub(crate) lenient: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("credentials", &self.credentials)
+            .field("base_url", &self.base_url)
+            .field("retry", &self.retry)
+            .field("cache", &self.cache.is_some())
+            .field("keys", &self.keys.as_ref().map(|keys| keys.len()))
+            .field(
+                "limiter",
+                &self.limiter.as_ref().map(|limiter| limiter.limit()),
+            )
+            .field(
+                "circuit_breaker",
+                &self.breaker.as_ref().map(|breaker| breaker.state()),
+            )
+            .field("coalesce", &self.coalescer.is_some())
+            .field("middleware", &self.middleware.len())
+            .field("lenient", &self.lenient)
+            .finish_non_exhaustive()
+    }
 }
 
 use helpers::to_query;
 
+impl Client {
+    /// Send `request`, a path and query for a `GET` request or a [`Request`] with any
+    /// method and body, and deserialize the response.
+    pub async fn rq<T: DeserializeOwned + Debug>(
+        &self,
+        request: impl Into<Request>,
+    ) -> Result<T, Error> {
+        self.rq_named("rq", request).await
+    }
+
+    /// Like [`rq`](Self::rq), but recorded in the client's metrics under `endpoint`.
+    pub async fn rq_named<T: DeserializeOwned + Debug>(
+        &self,
+        endpoint: &'static str,
+        request: impl Into<Request>,
+    ) -> Result<T, Error> {
+        Ok(self.rq_response_named(endpoint, request).await?.value)
+    }
+
+    /// Like [`rq`](Self::rq), but keeps the response's status, headers, timing and raw body.
+    ///
+    /// Responses served from the [`Cache`] report `200 OK` without headers.
+    pub async fn rq_response<T: DeserializeOwned + Debug>(
+        &self,
+        request: impl Into<Request>,
+    ) -> Result<ApiResponse<T>, Error> {
+        self.rq_response_named("rq", request).await
+    }
+
+    /// Deserialize a successful response to `path`. An empty body is read as `null`.
+    pub(crate) fn deserialize_response<T: DeserializeOwned + Debug>(
+        &self,
+        res: TransportResponse,
+        elapsed: Duration,
+        path: String,
+    ) -> Result<ApiResponse<T>, Error> {
+        let body = match res.body.trim() {
+            "" => "null",
+            _ => res.body.as_str(),
+        };
+        let deserialized = if self.lenient {
+            lenient::from_str(body)
+        } else {
+            let mut deserializer = serde_json::Deserializer::from_str(body);
+            serde_path_to_error::deserialize(&mut deserializer).map(|value| (value, Vec::new()))
+        };
+        let (value, warnings) = deserialized.map_err(|e| {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(at = %e.path(), error = %e.inner(), "failed to deserialize response");
+            Error::Deserialize {
+                path: e.path().to_string(),
+                source: e.into_inner(),
+                body: res.body.clone(),
+            }
+        })?;
+        #[cfg(feature = "tracing")]
+        for warning in &warnings {
+            tracing::warn!(at = %warning.path, error = %warning.message, "dropped value from response");
+        }
+        Ok(ApiResponse {
+            value,
+            raw_body: res.body,
+            status: res.status,
+            headers: res.headers,
+            elapsed,
+            request_path: path,
+            warnings,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
 impl Client {
     pub fn new(devid: String, token: String) -> Self {
-        Self { devid, token }
-    }
-    pub async fn rq<T: DeserializeOwned + Debug>(&self, path: String) -> Result<T> {
-        let path = format!(
-            "{path}{}devid={}",
-            {
-                if !path.contains('?') {
-                    "?"
-                } else if path.ends_with('?') {
-                    ""
-                } else {
-                    "&"
+        Self::with_transport(devid, token, ReqwestTransport::default())
+    }
+
+    /// Create a client that sends its requests through `transport` instead of [`reqwest`].
+    pub fn with_transport(
+        devid: String,
+        token: String,
+        transport: impl Transport + 'static,
+    ) -> Self {
+        ClientBuilder::new(devid, token).finish(Arc::new(transport))
+    }
+
+    /// Configure a client's base URL, timeouts, headers, proxy and retries. See [`ClientBuilder`].
+    pub fn builder(devid: impl Into<String>, token: impl Into<String>) -> ClientBuilder {
+        ClientBuilder::new(devid, token)
+    }
+
+    /// A client using the credentials in the environment, see [`Credentials::from_env`].
+    pub fn from_env() -> Result<Self, Error> {
+        ClientBuilder::from_env()?.build()
+    }
+
+    /// A client configured from a TOML file, see [`ClientBuilder::from_config_file`].
+    pub fn from_config_file(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        ClientBuilder::from_config_file(path)?.build()
+    }
+
+    /// The credentials the client was built with. Requests are signed with keys from the
+    /// [`key_pool`](Self::key_pool) instead when there is one.
+    pub fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    /// The developer keys requests are spread over, see [`ClientBuilder::key_pool`].
+    pub fn key_pool(&self) -> Option<&KeyPool> {
+        self.keys.as_deref()
+    }
+
+    /// The limiter enforcing [`ClientBuilder::rate_limit`], for its queueing statistics.
+    pub fn limiter(&self) -> Option<&Limiter> {
+        self.limiter.as_deref()
+    }
+
+    /// The breaker set with [`ClientBuilder::circuit_breaker`], to inspect or reset it.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.breaker.as_deref()
+    }
+
+    /// The response cache, for explicit invalidation. See [`ClientBuilder::cache`].
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    /// Like [`rq_response`](Self::rq_response), but recorded in the client's metrics under
+    /// `endpoint`.
+    pub async fn rq_response_named<T: DeserializeOwned + Debug>(
+        &self,
+        endpoint: &'static str,
+        request: impl Into<Request>,
+    ) -> Result<ApiResponse<T>, Error> {
+        self.respond_named(endpoint, request.into(), &CallOptions::default())
+            .await
+    }
+
+    /// Like [`rq`](Self::rq), with [`CallOptions`] for this call only.
+    pub async fn rq_with_options<T: DeserializeOwned + Debug>(
+        &self,
+        request: impl Into<Request>,
+        options: CallOptions,
+    ) -> Result<T, Error> {
+        self.rq_with_options_named("rq", request, options).await
+    }
+
+    /// Like [`rq_with_options`](Self::rq_with_options), but recorded in the client's
+    /// metrics under `endpoint`.
+    pub async fn rq_with_options_named<T: DeserializeOwned + Debug>(
+        &self,
+        endpoint: &'static str,
+        request: impl Into<Request>,
+        options: CallOptions,
+    ) -> Result<T, Error> {
+        Ok(self
+            .respond_named(endpoint, request.into(), &options)
+            .await?
+            .value)
+    }
+
+    async fn respond_named<T: DeserializeOwned + Debug>(
+        &self,
+        endpoint: &'static str,
+        request: Request,
+        options: &CallOptions,
+    ) -> Result<ApiResponse<T>, Error> {
+        let start = Instant::now();
+        let res = options.guard(start, self.respond(request, options)).await;
+        self.record(endpoint, start, &res);
+        res
+    }
+
+    #[cfg(feature = "metrics")]
+    fn record<T>(
+        &self,
+        endpoint: &'static str,
+        start: Instant,
+        res: &Result<ApiResponse<T>, Error>,
+    ) {
+        if let Some(metrics) = &self.metrics {
+            let outcome = res
+                .as_ref()
+                .map(|res| res.raw_body.len())
+                .map_err(Error::kind);
+            metrics.record(endpoint, start.elapsed(), outcome);
+        }
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    fn record<T>(&self, _: &'static str, _: Instant, _: &Result<ApiResponse<T>, Error>) {}
+
+    async fn respond<T: DeserializeOwned + Debug>(
+        &self,
+        request: Request,
+        options: &CallOptions,
+    ) -> Result<ApiResponse<T>, Error> {
+        let start = Instant::now();
+        let path = request.path.clone();
+        let res = self.dispatch(request, options).await?;
+        self.deserialize_response(res, start.elapsed(), path)
+    }
+
+    /// Run `request` through the middleware chain.
+    pub(crate) async fn dispatch(
+        &self,
+        request: Request,
+        options: &CallOptions,
+    ) -> Result<TransportResponse, Error> {
+        check_status(middleware::Next::new(self, options).run(request).await?)
+    }
+
+    /// Answer a `GET` request without headers from the cache, or [`send_once`](Self::send_once) it.
+    pub(crate) async fn fetch(
+        &self,
+        request: &Request,
+        options: &CallOptions,
+    ) -> Result<TransportResponse, Error> {
+        let cache = self.cache.as_ref().filter(|_| request.is_plain_get());
+        let cached = match cache {
+            Some(cache) if !options.bypass_cache => cache.get(&request.path),
+            _ => None,
+        };
+        if let Some(body) = cached {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(size = body.len(), "served from cache");
+            return Ok(TransportResponse::new(http::StatusCode::OK, body));
+        }
+        let res = self.send_guarded(request, options).await?;
+        if let Some(cache) = cache {
+            cache.put(&request.path, &res.body);
+        }
+        Ok(res)
+    }
+
+    /// [`send_once`](Self::send_once) `request` unless the [`CircuitBreaker`] is open.
+    async fn send_guarded(
+        &self,
+        request: &Request,
+        options: &CallOptions,
+    ) -> Result<TransportResponse, Error> {
+        let Some(breaker) = &self.breaker else {
+            return self.send_once(request, options).await;
+        };
+        match breaker.gate() {
+            circuit::Gate::Allow => {}
+            circuit::Gate::Reject { retry_in } => return Err(Error::CircuitOpen { retry_in }),
+            circuit::Gate::Probe(probe) => {
+                let probe_request = Request::new(&breaker.policy().probe_path);
+                let healthy =
+                    (self.attempt(&probe_request).await).is_ok_and(|res| circuit::healthy(&res));
+                probe.finish(healthy);
+                if !healthy {
+                    return Err(Error::CircuitOpen {
+                        retry_in: breaker.policy().probe_interval,
+                    });
                 }
-            },
-            self.devid
-        );
+            }
+        }
+        let res = self.send_once(request, options).await;
+        if let Some(failed) = circuit::failed(&res) {
+            breaker.record(failed);
+        }
+        res
+    }
 
-        let mut hasher: PtvHmac = Hmac::new_from_slice(self.token.as_bytes()).unwrap();
-        hasher.update(path.as_bytes());
+    /// [`send`](Self::send) `request`, or wait for an identical plain `GET` already in flight.
+    async fn send_once(
+        &self,
+        request: &Request,
+        options: &CallOptions,
+    ) -> Result<TransportResponse, Error> {
+        let coalescer = self.coalescer.as_ref().filter(|_| request.is_plain_get());
+        let Some(coalescer) = coalescer else {
+            return self.send(request, options).await;
+        };
+        loop {
+            match coalescer.join(&request.path) {
+                coalesce::Flight::Leader(leader) => {
+                    return leader.finish(self.send(request, options).await);
+                }
+                coalesce::Flight::Follower(mut flight) => {
+                    // Only fails if the leader was cancelled, in which case take over.
+                    if let Ok(res) = flight.recv().await {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!("shared an identical request in flight");
+                        return coalesce::unshare(res);
+                    }
+                }
+            }
+        }
+    }
 
-        let hash = hex::encode(hasher.finalize().into_bytes()).to_uppercase();
-        let url = format!("{API_URL}{}&signature={}", path, hash);
+    /// Send `request`, retrying according to the call's or else the client's [`RetryPolicy`].
+    /// Requests that aren't idempotent, such as `POST`, are sent once.
+    async fn send(
+        &self,
+        request: &Request,
+        options: &CallOptions,
+    ) -> Result<TransportResponse, Error> {
+        let policy = match &options.retry {
+            Some(policy) => policy.as_ref(),
+            None => self.retry.as_ref(),
+        };
+        let Some(policy) = policy.filter(|_| request.method.is_idempotent()) else {
+            return self.attempt(request).await;
+        };
 
-        if std::env::var("DEBUG").is_ok() {
-            println!("Requesting: |{}|", url);
+        let mut attempt = 1;
+        loop {
+            match self.attempt(request).await {
+                Ok(res) => return Ok(res),
+                Err(e) if !policy.is_retryable(&e) => return Err(e),
+                // A policy allowing a single attempt never retried anything.
+                Err(e) if policy.max_attempts <= 1 => return Err(e),
+                Err(e) if attempt >= policy.max_attempts => {
+                    return Err(Error::RetriesExhausted {
+                        attempts: attempt,
+                        source: Box::new(e),
+                    });
+                }
+                Err(e) => {
+                    let headers = match &e {
+                        Error::Status { headers, .. } => Some(headers.as_ref()),
+                        _ => None,
+                    };
+                    let delay = policy.delay(attempt, headers);
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(attempt, ?delay, error = %e, "retrying request");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
         }
+    }
 
-        let res = reqwest::get(&url).await?;
-        if !res.status().is_success() {
-            let status = res.status();
-            if let Ok(ApiError { message, .. }) = res.json().await {
-                return Err(anyhow::anyhow!("Request failed: {} - {}", status, message));
+    /// Sign `request` and send it once, or once per key while a [`KeyPool`] fails over.
+    async fn attempt(&self, request: &Request) -> Result<TransportResponse, Error> {
+        let Some(keys) = &self.keys else {
+            return self.attempt_with(&self.credentials, request).await;
+        };
+        loop {
+            let (index, credentials) = keys.select().ok_or_else(|| {
+                Error::Config("Every developer key in the pool is disabled".to_string())
+            })?;
+            match self.attempt_with(&credentials, request).await {
+                Err(e) if e.status() == Some(http::StatusCode::FORBIDDEN) => {
+                    let retry = keys.reject(index);
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(key = index, retry, "developer key rejected");
+                    if !retry {
+                        return Err(e);
+                    }
+                }
+                res => return res,
             }
-            return Err(anyhow::anyhow!("Request failed: {}", status));
         }
-        //        println!("{}", res.text().await?);
-        let res = res.text().await?;
-        let mut deserializer = serde_json::Deserializer::from_str(&res);
-
-        let res: T = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
-            anyhow::anyhow!(
-                "Error at path: {} {} - response: {}",
-                e.path(),
-                e.inner(),
-                res
-            )
-        })?;
-        Ok(res)
     }
+
+    async fn attempt_with(
+        &self,
+        credentials: &Credentials,
+        request: &Request,
+    ) -> Result<TransportResponse, Error> {
+        let url = signing::sign_path(credentials.devid(), credentials.key(), &request.path)
+            .to_url(&self.base_url);
+
+        let permit = match &self.limiter {
+            Some(limiter) => Some(limiter.acquire().await),
+            None => None,
+        };
+        #[cfg(feature = "tracing")]
+        let start = Instant::now();
+        let res = self.transport.send_request(request, &url).await;
+        drop(permit);
+        #[cfg(feature = "tracing")]
+        match &res {
+            Ok(res) => tracing::debug!(
+                method = %request.method,
+                url = %signing::redact(&url),
+                status = res.status.as_u16(),
+                latency = ?start.elapsed(),
+                size = res.body.len(),
+                "received response"
+            ),
+            Err(e) => tracing::warn!(
+                method = %request.method,
+                url = %signing::redact(&url),
+                latency = ?start.elapsed(),
+                error = %e,
+                "request failed"
+            ),
+        }
+
+        check_status(res?)
+    }
+}
+
+/// `res`, or [`Error::Status`] if it isn't a success.
+pub(crate) fn check_status(res: TransportResponse) -> Result<TransportResponse, Error> {
+    if res.status.is_success() {
+        return Ok(res);
+    }
+    Err(Error::Status {
+        status: res.status,
+        error: serde_json::from_str(&res.body).ok(),
+        headers: Box::new(res.headers),
+        body: res.body,
+    })
 }
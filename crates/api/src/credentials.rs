@@ -0,0 +1,75 @@
+//! Developer ID and key handling.
+//!
+//! [`Credentials`] never print their values: `Debug` and `Display` are redacted, and the
+//! memory holding them is zeroed when they are dropped.
+
+use std::fmt;
+
+use serde::Deserialize;
+use zeroize::Zeroizing;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::Error;
+
+/// Environment variable holding the developer ID, see [`Credentials::from_env`].
+pub const DEVID_ENV: &str = "DEVID";
+/// Environment variable holding the developer key, see [`Credentials::from_env`].
+pub const KEY_ENV: &str = "KEY";
+
+/// A PTV developer ID and the key requests are signed with.
+#[derive(Clone, Deserialize)]
+pub struct Credentials {
+    devid: Zeroizing<String>,
+    #[serde(alias = "token")]
+    key: Zeroizing<String>,
+}
+
+impl Credentials {
+    pub fn new(devid: impl Into<String>, key: impl Into<String>) -> Self {
+        Self {
+            devid: Zeroizing::new(devid.into()),
+            key: Zeroizing::new(key.into()),
+        }
+    }
+
+    pub fn devid(&self) -> &str {
+        &self.devid
+    }
+
+    /// The developer key. Take care not to log it.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Read [`DEVID_ENV`] and [`KEY_ENV`], loading a `.env` file first if either is unset.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_env() -> Result<Self, Error> {
+        let read = || Some((std::env::var(DEVID_ENV).ok()?, std::env::var(KEY_ENV).ok()?));
+        let (devid, key) = read()
+            .or_else(|| {
+                dotenv::dotenv().ok();
+                read()
+            })
+            .ok_or_else(|| {
+                Error::Config(format!(
+                    "{DEVID_ENV} and {KEY_ENV} must be set in the environment or a .env file"
+                ))
+            })?;
+        Ok(Self::new(devid, key))
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("devid", &"REDACTED")
+            .field("key", &"REDACTED")
+            .finish()
+    }
+}
+
+impl fmt::Display for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Credentials(REDACTED)")
+    }
+}
@@ -0,0 +1,284 @@
+//! Checking responses against the swagger spec, to find where the API has drifted from it.
+//!
+//! [`check`] compares a raw response body with the response schema of the operation that
+//! was requested, and lists every field that is missing, undeclared, of the wrong type, or
+//! holds an enum value the spec doesn't declare. Each [`Drift`] names the spec definition it
+//! was found in, so running this over recorded fixtures or the mock server shows which
+//! generated types need an `extra_names` override.
+//!
+//! `null` is accepted for any field, since every generated field is an `Option`.
+//!
+//! ```no_run
+//! # async fn run(client: ptv::Client) -> Result<(), ptv::Error> {
+//! use ptv::{RouteId, drift, spec::Spec};
+//!
+//! let spec = Spec::v3();
+//! let res = client.get_directions_by_route_id_with_response(RouteId(1)).await?;
+//! let report = drift::check_response(&spec, &res)?;
+//! for definition in report.definitions() {
+//!     println!("{definition} has drifted");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::BTreeSet, fmt};
+
+use serde_json::Value;
+
+use crate::{ApiResponse, Error, spec::Spec};
+
+/// How a response differs from its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DriftKind {
+    /// A field listed as `required` is absent.
+    MissingField {
+        field: String,
+    },
+    /// A field the schema doesn't declare.
+    ExtraField {
+        field: String,
+        value: Value,
+    },
+    TypeMismatch {
+        expected: String,
+        found: String,
+    },
+    UndeclaredEnumValue {
+        value: Value,
+        allowed: Vec<Value>,
+    },
+}
+
+/// A single difference between a response and its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    /// Where in the response, e.g. `departures[3].scheduled_departure_utc`.
+    pub path: String,
+    /// The innermost spec definition containing the drift, e.g. `V3.Departure`.
+    pub definition: Option<String>,
+    pub kind: DriftKind,
+}
+
+/// Every [`Drift`] found in one response.
+#[derive(Debug, Clone)]
+pub struct DriftReport {
+    /// The path and query that was requested.
+    pub request_path: String,
+    /// The spec path template it matched, e.g. `/v3/routes/{route_id}`.
+    pub template: String,
+    pub drifts: Vec<Drift>,
+}
+
+impl DriftReport {
+    /// Whether the response matched its schema.
+    pub fn is_clean(&self) -> bool {
+        self.drifts.is_empty()
+    }
+
+    /// The spec definitions that differ from the response.
+    pub fn definitions(&self) -> BTreeSet<&str> {
+        self.drifts
+            .iter()
+            .filter_map(|drift| drift.definition.as_deref())
+            .collect()
+    }
+}
+
+impl fmt::Display for DriftKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DriftKind::MissingField { field } => write!(f, "missing required field `{field}`"),
+            DriftKind::ExtraField { field, .. } => write!(f, "undeclared field `{field}`"),
+            DriftKind::TypeMismatch { expected, found } => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            DriftKind::UndeclaredEnumValue { value, allowed } => {
+                let allowed = allowed.iter().map(Value::to_string).collect::<Vec<_>>();
+                write!(f, "{value} is not one of {}", allowed.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "<root>"
+        } else {
+            &self.path
+        };
+        match &self.definition {
+            Some(definition) => write!(f, "{path} ({definition}): {}", self.kind),
+            None => write!(f, "{path}: {}", self.kind),
+        }
+    }
+}
+
+impl fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.template)?;
+        if self.is_clean() {
+            return write!(f, ": no drift");
+        }
+        for drift in &self.drifts {
+            write!(f, "\n  {drift}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare `body`, received for `request_path`, with the operation's response schema.
+///
+/// Fails if no operation in `spec` matches `request_path`, or `body` isn't JSON.
+pub fn check(spec: &Spec, request_path: &str, body: &str) -> Result<DriftReport, Error> {
+    let path = request_path.split('?').next().unwrap_or_default();
+    let no_schema = || Error::InvalidArgument(format!("No response schema for {path} in the spec"));
+    let template = spec.find(path).ok_or_else(no_schema)?;
+    let schema = spec.response_schema(template).ok_or_else(no_schema)?;
+    let value: Value = serde_json::from_str(body).map_err(|source| Error::Deserialize {
+        path: String::new(),
+        source,
+        body: body.to_string(),
+    })?;
+
+    let mut checker = Checker {
+        spec,
+        drifts: Vec::new(),
+    };
+    checker.check(schema, &value, String::new(), None);
+    Ok(DriftReport {
+        request_path: request_path.to_string(),
+        template: template.to_string(),
+        drifts: checker.drifts,
+    })
+}
+
+/// [`check`] a response returned by a `*_with_response` method.
+pub fn check_response<T>(spec: &Spec, response: &ApiResponse<T>) -> Result<DriftReport, Error> {
+    check(spec, &response.request_path, &response.raw_body)
+}
+
+struct Checker<'a> {
+    spec: &'a Spec,
+    drifts: Vec<Drift>,
+}
+
+impl<'a> Checker<'a> {
+    fn check(
+        &mut self,
+        schema: &'a Value,
+        value: &Value,
+        path: String,
+        definition: Option<&'a str>,
+    ) {
+        let definition = schema["$ref"]
+            .as_str()
+            .and_then(|reference| reference.strip_prefix("#/definitions/"))
+            .or(definition);
+        let schema = self.spec.resolve(schema);
+        if value.is_null() {
+            return;
+        }
+        if let Some(allowed) = schema["enum"].as_array()
+            && !allowed.contains(value)
+        {
+            let kind = DriftKind::UndeclaredEnumValue {
+                value: value.clone(),
+                allowed: allowed.clone(),
+            };
+            return self.report(path, definition, kind);
+        }
+        let expected = match schema["type"].as_str() {
+            Some(expected) => expected,
+            None if schema.get("properties").is_some() => "object",
+            None => return,
+        };
+        let matches = match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            _ => true,
+        };
+        if !matches {
+            let kind = DriftKind::TypeMismatch {
+                expected: expected.to_string(),
+                found: type_name(value).to_string(),
+            };
+            return self.report(path, definition, kind);
+        }
+
+        if let Some(items) = value.as_array() {
+            for (index, item) in items.iter().enumerate() {
+                self.check(
+                    &schema["items"],
+                    item,
+                    format!("{path}[{index}]"),
+                    definition,
+                );
+            }
+        } else if let Some(fields) = value.as_object() {
+            let properties = schema["properties"].as_object();
+            for field in schema["required"].as_array().into_iter().flatten() {
+                if let Some(field) = field.as_str()
+                    && !fields.contains_key(field)
+                {
+                    let kind = DriftKind::MissingField {
+                        field: field.to_string(),
+                    };
+                    self.report(path.clone(), definition, kind);
+                }
+            }
+            for (field, value) in fields {
+                let child = if path.is_empty() {
+                    field.clone()
+                } else {
+                    format!("{path}.{field}")
+                };
+                match (
+                    properties.and_then(|p| p.get(field)),
+                    &schema["additionalProperties"],
+                ) {
+                    (Some(property), _) => self.check(property, value, child, definition),
+                    (None, additional @ Value::Object(_)) => {
+                        self.check(additional, value, child, definition)
+                    }
+                    (None, Value::Bool(true)) => {}
+                    (None, _) if properties.is_none() => {}
+                    (None, _) => {
+                        let kind = DriftKind::ExtraField {
+                            field: field.clone(),
+                            value: value.clone(),
+                        };
+                        self.report(child, definition, kind);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Checker<'_> {
+    fn report(&mut self, path: String, definition: Option<&str>, kind: DriftKind) {
+        self.drifts.push(Drift {
+            path,
+            definition: definition.map(str::to_string),
+            kind,
+        });
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
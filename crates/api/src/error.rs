@@ -0,0 +1,150 @@
+use std::{fmt, time::Duration};
+
+use http::{HeaderMap, StatusCode};
+
+use crate::ApiError;
+
+/// Errors produced by a [`Transport`](crate::Transport) before a response was received.
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Everything that can go wrong when calling the PTV API.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The request could not be sent, or the response could not be read.
+    Transport(TransportError),
+    /// The API answered with a non-success status code.
+    /// `error` holds the API's own error payload when the body could be parsed as one.
+    Status {
+        status: StatusCode,
+        headers: Box<HeaderMap>,
+        error: Option<ApiError>,
+        body: String,
+    },
+    /// The response body did not match the expected type.
+    Deserialize {
+        /// Path to the offending value, as reported by [`serde_path_to_error`].
+        path: String,
+        source: serde_json::Error,
+        body: String,
+    },
+    /// An argument could not be turned into a request.
+    InvalidArgument(String),
+    /// Credentials or a configuration file could not be loaded.
+    Config(String),
+    /// The [`RetryPolicy`](crate::RetryPolicy) gave up; `source` is the error from the final attempt.
+    RetriesExhausted { attempts: u32, source: Box<Error> },
+    /// The [`CircuitBreaker`](crate::CircuitBreaker) is open, so the request wasn't sent.
+    /// The API will be probed again in `retry_in`.
+    CircuitOpen { retry_in: Duration },
+    /// The call's [deadline](crate::CallOptions::deadline) passed after `elapsed`.
+    Timeout { elapsed: Duration },
+    /// The call's [cancellation token](crate::CallOptions::cancel_on) was cancelled.
+    Cancelled,
+}
+
+impl Error {
+    /// The HTTP status code, if the API responded with an error status.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::Status { status, .. } => Some(*status),
+            Error::RetriesExhausted { source, .. } => source.status(),
+            _ => None,
+        }
+    }
+
+    /// A short, stable name for the variant, e.g. for labelling metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Transport(_) => "transport",
+            Error::Status { .. } => "status",
+            Error::Deserialize { .. } => "deserialize",
+            Error::InvalidArgument(_) => "invalid_argument",
+            Error::Config(_) => "config",
+            Error::RetriesExhausted { .. } => "retries_exhausted",
+            Error::CircuitOpen { .. } => "circuit_open",
+            Error::Timeout { .. } => "timeout",
+            Error::Cancelled => "cancelled",
+        }
+    }
+
+    /// A copy of the error, for handing to several callers. Errors that can't be cloned,
+    /// such as transport errors, are copied as their message.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn duplicate(&self) -> Error {
+        match self {
+            Error::Transport(e) => Error::Transport(e.to_string().into()),
+            Error::Status {
+                status,
+                headers,
+                error,
+                body,
+            } => Error::Status {
+                status: *status,
+                headers: headers.clone(),
+                error: error.clone(),
+                body: body.clone(),
+            },
+            Error::Deserialize { path, source, body } => Error::Deserialize {
+                path: path.clone(),
+                source: serde::de::Error::custom(source),
+                body: body.clone(),
+            },
+            Error::InvalidArgument(message) => Error::InvalidArgument(message.clone()),
+            Error::Config(message) => Error::Config(message.clone()),
+            Error::RetriesExhausted { attempts, source } => Error::RetriesExhausted {
+                attempts: *attempts,
+                source: Box::new(source.duplicate()),
+            },
+            Error::CircuitOpen { retry_in } => Error::CircuitOpen {
+                retry_in: *retry_in,
+            },
+            Error::Timeout { elapsed } => Error::Timeout { elapsed: *elapsed },
+            Error::Cancelled => Error::Cancelled,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport(e) => write!(f, "Request failed: {e}"),
+            Error::Status {
+                status,
+                error: Some(ApiError { message, .. }),
+                ..
+            } => write!(f, "Request failed: {status} - {message}"),
+            Error::Status { status, .. } => write!(f, "Request failed: {status}"),
+            Error::Deserialize {
+                path, source, body, ..
+            } => write!(f, "Error at path: {path} {source} - response: {body}"),
+            Error::InvalidArgument(message) => write!(f, "Invalid argument: {message}"),
+            Error::Config(message) => write!(f, "Invalid configuration: {message}"),
+            Error::RetriesExhausted { attempts, source } => {
+                write!(f, "Gave up after {attempts} attempts: {source}")
+            }
+            Error::CircuitOpen { retry_in } => {
+                write!(f, "Circuit breaker is open, retrying in {retry_in:?}")
+            }
+            Error::Timeout { elapsed } => write!(f, "Request timed out after {elapsed:?}"),
+            Error::Cancelled => write!(f, "Request was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport(e) => Some(e.as_ref()),
+            Error::Deserialize { source, .. } => Some(source),
+            Error::RetriesExhausted { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<TransportError> for Error {
+    fn from(e: TransportError) -> Self {
+        Error::Transport(e)
+    }
+}
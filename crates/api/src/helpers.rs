@@ -3,7 +3,7 @@ use itertools::Itertools;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de::SeqAccess, ser};
 
-use crate::DisruptionMode;
+use crate::{DisruptionMode, Error};
 
 pub fn clean(s: String) -> String {
     let mut s = s;
@@ -13,11 +13,12 @@ pub fn clean(s: String) -> String {
     s
 }
 
-pub fn to_query<T: Serialize>(s: T) -> String {
-    serde_json::to_value(s)
-        .unwrap()
-        .as_object()
-        .unwrap()
+pub fn to_query<T: Serialize>(s: T) -> Result<String, Error> {
+    let value = serde_json::to_value(s).map_err(|e| Error::InvalidArgument(e.to_string()))?;
+    let params = value.as_object().ok_or_else(|| {
+        Error::InvalidArgument(format!("query parameters must be an object, got {value}"))
+    })?;
+    Ok(params
         .iter()
         .map(|(k, v)| {
             // If v is an array, define k={v[0]}&k={v[1]}&...
@@ -39,7 +40,7 @@ pub fn to_query<T: Serialize>(s: T) -> String {
             }
         })
         .collect::<Vec<String>>()
-        .join("&")
+        .join("&"))
 }
 
 pub fn de_iso_8601<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
@@ -0,0 +1,187 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Spreading requests over several developer keys.
+//!
+//! A [`KeyPool`] holds any number of [`Credentials`] and picks one for every request
+//! according to its [`KeyStrategy`]. Keys can be disabled and re-enabled at runtime, and
+//! each key counts the requests it signed and how often the API rejected it.
+
+use std::{sync::Mutex, time::Instant};
+
+use crate::Credentials;
+
+/// How a [`KeyPool`] picks the key for the next request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStrategy {
+    /// Cycle through the enabled keys in order.
+    RoundRobin,
+    /// Use the enabled key that has gone unused the longest.
+    LeastRecentlyUsed,
+    /// Use the first enabled key until the API rejects it with `403 Forbidden`,
+    /// then disable it and move on to the next.
+    Failover,
+}
+
+/// Usage of a single key in a [`KeyPool`].
+#[derive(Debug, Clone)]
+pub struct KeyStats {
+    pub devid: String,
+    pub enabled: bool,
+    /// Requests signed with this key, including retries.
+    pub requests: u64,
+    /// Responses rejecting this key with `403 Forbidden`.
+    pub rejections: u64,
+    pub last_used: Option<Instant>,
+}
+
+struct KeyState {
+    credentials: Credentials,
+    enabled: bool,
+    requests: u64,
+    rejections: u64,
+    last_used: Option<Instant>,
+    /// Value of [`PoolState::tick`] when the key was last used, 0 if never.
+    used_tick: u64,
+}
+
+struct PoolState {
+    keys: Vec<KeyState>,
+    /// Index the next round-robin search starts at.
+    cursor: usize,
+    /// Incremented on every selection, so least-recently-used never ties.
+    tick: u64,
+}
+
+pub struct KeyPool {
+    strategy: KeyStrategy,
+    disable_on_forbidden: bool,
+    state: Mutex<PoolState>,
+}
+
+impl KeyPool {
+    pub fn new(keys: impl IntoIterator<Item = Credentials>, strategy: KeyStrategy) -> Self {
+        let keys = keys
+            .into_iter()
+            .map(|credentials| KeyState {
+                credentials,
+                enabled: true,
+                requests: 0,
+                rejections: 0,
+                last_used: None,
+                used_tick: 0,
+            })
+            .collect();
+        Self {
+            strategy,
+            disable_on_forbidden: strategy == KeyStrategy::Failover,
+            state: Mutex::new(PoolState {
+                keys,
+                cursor: 0,
+                tick: 0,
+            }),
+        }
+    }
+
+    /// Disable a key as soon as the API rejects it, and retry the request with another one.
+    /// Always on for [`KeyStrategy::Failover`], off by default otherwise.
+    pub fn disable_on_forbidden(mut self, disable: bool) -> Self {
+        self.disable_on_forbidden = disable || self.strategy == KeyStrategy::Failover;
+        self
+    }
+
+    pub fn strategy(&self) -> KeyStrategy {
+        self.strategy
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stop using the key for `devid`. Returns whether such a key exists.
+    pub fn disable(&self, devid: &str) -> bool {
+        self.set_enabled(devid, false)
+    }
+
+    /// Start using the key for `devid` again. Returns whether such a key exists.
+    pub fn enable(&self, devid: &str) -> bool {
+        self.set_enabled(devid, true)
+    }
+
+    pub fn stats(&self) -> Vec<KeyStats> {
+        self.state
+            .lock()
+            .unwrap()
+            .keys
+            .iter()
+            .map(|key| KeyStats {
+                devid: key.credentials.devid().to_string(),
+                enabled: key.enabled,
+                requests: key.requests,
+                rejections: key.rejections,
+                last_used: key.last_used,
+            })
+            .collect()
+    }
+
+    fn set_enabled(&self, devid: &str, enabled: bool) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let mut found = false;
+        for key in state
+            .keys
+            .iter_mut()
+            .filter(|key| key.credentials.devid() == devid)
+        {
+            key.enabled = enabled;
+            found = true;
+        }
+        found
+    }
+
+    /// Pick the key for the next request and count it as used.
+    pub(crate) fn select(&self) -> Option<(usize, Credentials)> {
+        let mut state = self.state.lock().unwrap();
+        let len = state.keys.len();
+        let index = match self.strategy {
+            KeyStrategy::RoundRobin => {
+                let index = (0..len)
+                    .map(|offset| (state.cursor + offset) % len)
+                    .find(|&i| state.keys[i].enabled)?;
+                state.cursor = index + 1;
+                index
+            }
+            KeyStrategy::LeastRecentlyUsed => state
+                .keys
+                .iter()
+                .enumerate()
+                .filter(|(_, key)| key.enabled)
+                .min_by_key(|(_, key)| key.used_tick)
+                .map(|(i, _)| i)?,
+            KeyStrategy::Failover => state.keys.iter().position(|key| key.enabled)?,
+        };
+        state.tick += 1;
+        let tick = state.tick;
+        let key = &mut state.keys[index];
+        key.requests += 1;
+        key.used_tick = tick;
+        key.last_used = Some(Instant::now());
+        Some((index, key.credentials.clone()))
+    }
+
+    /// Record that the API rejected the key at `index`. Returns whether the request
+    /// should be retried with another key.
+    pub(crate) fn reject(&self, index: usize) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(key) = state.keys.get_mut(index) else {
+            return false;
+        };
+        key.rejections += 1;
+        if !self.disable_on_forbidden {
+            return false;
+        }
+        key.enabled = false;
+        state.keys.iter().any(|key| key.enabled)
+    }
+}
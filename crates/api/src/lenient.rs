@@ -0,0 +1,162 @@
+//! Lenient deserialization, for responses that have drifted from the spec.
+//!
+//! In lenient mode (see [`ClientBuilder::lenient`](crate::ClientBuilder::lenient)) a value
+//! that fails to deserialize doesn't fail the whole call. The offending field is treated as
+//! missing, so an `Option` becomes `None`; if the field can't be missing, the nearest
+//! enclosing optional field is dropped instead, or the enclosing list element if there is
+//! one. Every value dropped this way is reported as a [`DeserializeWarning`].
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_path_to_error::{Path, Segment};
+
+/// One step of a [`Path`] into a JSON value.
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Index(usize),
+    Key(String),
+}
+
+/// A response holds at most this many values to drop before lenient deserialization gives up.
+const MAX_WARNINGS: usize = 256;
+
+/// A value that was dropped because it didn't match the expected type.
+#[derive(Debug, Clone)]
+pub struct DeserializeWarning {
+    /// Where the value was, e.g. `departures[3].scheduled_departure_utc`.
+    pub path: String,
+    pub message: String,
+    /// The value that was dropped.
+    pub value: Value,
+}
+
+/// Deserialize `body`, dropping values that don't fit `T` rather than failing.
+///
+/// Only fails when the body isn't JSON or the top-level value can't be salvaged.
+pub fn from_str<T: DeserializeOwned>(
+    body: &str,
+) -> Result<(T, Vec<DeserializeWarning>), serde_path_to_error::Error<serde_json::Error>> {
+    let mut value: Value =
+        serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(body))?;
+    let mut warnings: Vec<DeserializeWarning> = Vec::new();
+    let mut nulled: Option<Vec<Step>> = None;
+    let mut removed = Removed::default();
+    loop {
+        let e = match serde_path_to_error::deserialize(&value) {
+            Ok(res) => return Ok((res, warnings)),
+            Err(e) => e,
+        };
+        let Some(mut target) = steps(e.path()) else {
+            return Err(e);
+        };
+        let mut message = e.inner().to_string();
+        // A field that still fails after being nulled can't be missing, so drop its parent
+        // instead, keeping the original value and message in the warning.
+        if nulled.as_ref() == Some(&target)
+            && let Some(previous) = warnings.pop()
+        {
+            if let Some(slot) = lookup(&mut value, &target) {
+                *slot = previous.value;
+            }
+            target.pop();
+            message = previous.message;
+        }
+        if target.is_empty() || warnings.len() >= MAX_WARNINGS {
+            return Err(e);
+        }
+        let Some(dropped) = drop_value(&mut value, &target) else {
+            return Err(e);
+        };
+        let original = removed.original(&target);
+        if let Some((Step::Index(index), list)) = original.split_last() {
+            removed.0.push((list.to_vec(), *index));
+        }
+        warnings.push(DeserializeWarning {
+            path: display(&original),
+            message,
+            value: dropped,
+        });
+        nulled = matches!(target.last(), Some(Step::Key(_))).then_some(target);
+    }
+}
+
+/// List elements removed so far, as the path of the list and the element's index, both as
+/// they were in the response body.
+#[derive(Default)]
+struct Removed(Vec<(Vec<Step>, usize)>);
+
+impl Removed {
+    /// `path` in the value as it is now, translated to the path in the response body.
+    fn original(&self, path: &[Step]) -> Vec<Step> {
+        let mut original = Vec::with_capacity(path.len());
+        for step in path {
+            let step = match step {
+                Step::Index(index) => {
+                    let mut removed: Vec<usize> = (self.0.iter())
+                        .filter(|(list, _)| *list == original)
+                        .map(|(_, index)| *index)
+                        .collect();
+                    removed.sort_unstable();
+                    let mut index = *index;
+                    for removed in removed {
+                        if removed <= index {
+                            index += 1;
+                        }
+                    }
+                    Step::Index(index)
+                }
+                Step::Key(key) => Step::Key(key.clone()),
+            };
+            original.push(step);
+        }
+        original
+    }
+}
+
+/// `path` as steps into a JSON value, or `None` if it can't be followed.
+fn steps(path: &Path) -> Option<Vec<Step>> {
+    path.iter()
+        .map(|segment| match segment {
+            Segment::Seq { index } => Some(Step::Index(*index)),
+            Segment::Map { key } => Some(Step::Key(key.clone())),
+            Segment::Enum { .. } | Segment::Unknown => None,
+        })
+        .collect()
+}
+
+fn display(steps: &[Step]) -> String {
+    let mut path = String::new();
+    for step in steps {
+        match step {
+            Step::Index(index) => path.push_str(&format!("[{index}]")),
+            Step::Key(key) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+        }
+    }
+    path
+}
+
+fn lookup<'a>(value: &'a mut Value, path: &[Step]) -> Option<&'a mut Value> {
+    path.iter().try_fold(value, |value, step| match step {
+        Step::Index(index) => value.get_mut(*index),
+        Step::Key(key) => value.get_mut(key.as_str()),
+    })
+}
+
+/// Remove the value at `path`: list elements are taken out of the list,
+/// object fields are replaced with `null`. Returns the original value.
+fn drop_value(value: &mut Value, path: &[Step]) -> Option<Value> {
+    let (last, parents) = path.split_last()?;
+    let parent = lookup(value, parents)?;
+    match last {
+        Step::Index(index) => {
+            let list = parent.as_array_mut()?;
+            (*index < list.len()).then(|| list.remove(*index))
+        }
+        Step::Key(key) => Some(parent.get_mut(key.as_str())?.take()),
+    }
+}
@@ -1,8 +1,92 @@
-#[cfg(not(target_arch = "wasm32"))]
 pub mod core;
-#[cfg(not(target_arch = "wasm32"))]
 pub use core::*;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod builder;
+#[cfg(not(target_arch = "wasm32"))]
+pub use builder::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
+#[cfg(not(target_arch = "wasm32"))]
+pub use cache::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod circuit;
+#[cfg(not(target_arch = "wasm32"))]
+pub use circuit::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod coalesce;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cassette;
+#[cfg(not(target_arch = "wasm32"))]
+pub use cassette::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod key_pool;
+#[cfg(not(target_arch = "wasm32"))]
+pub use key_pool::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod limit;
+#[cfg(not(target_arch = "wasm32"))]
+pub use limit::*;
+
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+pub mod metrics;
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+pub use metrics::Metrics;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod middleware;
+#[cfg(not(target_arch = "wasm32"))]
+pub use middleware::Middleware;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod options;
+#[cfg(not(target_arch = "wasm32"))]
+pub use options::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod retry;
+#[cfg(not(target_arch = "wasm32"))]
+pub use retry::*;
+
+pub mod transport;
+pub use transport::*;
+
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
+#[cfg(target_arch = "wasm32")]
+pub use wasm::{FetchTransport, Signer};
+
+pub mod drift;
+
+pub mod error;
+pub use error::*;
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+
+pub mod credentials;
+pub use credentials::*;
+
+pub mod lenient;
+pub use lenient::DeserializeWarning;
+
+pub mod request;
+pub use request::Request;
+
+pub mod response;
+pub use response::*;
+
+pub mod spec;
+
+pub mod signing;
+pub use signing::SignedUrl;
+
 pub mod helpers;
 pub use helpers::*;
 
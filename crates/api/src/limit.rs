@@ -0,0 +1,215 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Client-side limits on how fast requests are sent.
+//!
+//! A [`RateLimit`] caps the number of requests in flight and/or the number of requests
+//! started per second (a token bucket). Requests wait their turn in the order they arrived,
+//! so a large fan-out can't starve a request issued after it began. Retries wait again.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::Error;
+
+/// Opt-in request limits for [`Client`](crate::Client), set with
+/// [`ClientBuilder::rate_limit`](crate::ClientBuilder::rate_limit).
+#[derive(Debug, Clone, Default)]
+pub struct RateLimit {
+    /// Most requests awaiting a response at once.
+    pub max_in_flight: Option<usize>,
+    /// Average number of requests started per second.
+    pub requests_per_second: Option<f64>,
+    /// Requests that may start back to back before `requests_per_second` applies.
+    /// At least 1.
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_in_flight(mut self, max: usize) -> Self {
+        self.max_in_flight = Some(max);
+        self
+    }
+
+    pub fn requests_per_second(mut self, rate: f64) -> Self {
+        self.requests_per_second = Some(rate);
+        self
+    }
+
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        if self.max_in_flight == Some(0) {
+            return Err(Error::Config(
+                "max_in_flight must be at least 1".to_string(),
+            ));
+        }
+        // The wait for a token must fit in a `Duration`, which rules out tiny rates too.
+        if let Some(rate) = self.requests_per_second
+            && !(rate.is_finite() && rate > 0.0 && Duration::try_from_secs_f64(1.0 / rate).is_ok())
+        {
+            return Err(Error::Config(format!(
+                "requests_per_second must be positive and not vanishingly small, got {rate}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Queueing at a [`Limiter`] so far.
+#[derive(Debug, Clone, Default)]
+pub struct LimiterStats {
+    /// Requests currently being sent.
+    pub in_flight: usize,
+    /// Requests currently waiting for their turn.
+    pub queued: usize,
+    /// Requests let through, including retries.
+    pub requests: u64,
+    /// Time spent waiting, summed over all requests.
+    pub total_wait: Duration,
+    /// Longest time any single request waited.
+    pub max_wait: Duration,
+}
+
+impl LimiterStats {
+    pub fn mean_wait(&self) -> Duration {
+        match self.requests {
+            0 => Duration::ZERO,
+            requests => self.total_wait.div_f64(requests as f64),
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    rate: f64,
+    refilled: tokio::time::Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self) {
+        let now = tokio::time::Instant::now();
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.refilled = now;
+    }
+}
+
+/// Enforces a [`RateLimit`]. Shared by every clone of a [`Client`](crate::Client).
+pub struct Limiter {
+    limit: RateLimit,
+    in_flight: Option<Semaphore>,
+    /// Held while waiting for a token; tokio's mutex queues waiters in order.
+    bucket: Option<tokio::sync::Mutex<Bucket>>,
+    stats: Mutex<LimiterStats>,
+}
+
+/// Counts a request as queued until dropped, including when the wait is cancelled.
+struct Queued<'a>(&'a Limiter);
+
+impl<'a> Queued<'a> {
+    fn new(limiter: &'a Limiter) -> Self {
+        limiter.stats.lock().unwrap().queued += 1;
+        Self(limiter)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.stats.lock().unwrap().queued -= 1;
+    }
+}
+
+/// A request's turn at the [`Limiter`], given back when dropped.
+pub(crate) struct Permit<'a> {
+    limiter: &'a Limiter,
+    _in_flight: Option<SemaphorePermit<'a>>,
+}
+
+impl Limiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        let capacity = limit.burst.max(1) as f64;
+        Self {
+            in_flight: limit.max_in_flight.map(Semaphore::new),
+            bucket: limit.requests_per_second.map(|rate| {
+                tokio::sync::Mutex::new(Bucket {
+                    tokens: capacity,
+                    capacity,
+                    rate,
+                    refilled: tokio::time::Instant::now(),
+                })
+            }),
+            stats: Mutex::new(LimiterStats::default()),
+            limit,
+        }
+    }
+
+    pub fn limit(&self) -> &RateLimit {
+        &self.limit
+    }
+
+    pub fn stats(&self) -> LimiterStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Wait for a free in-flight slot, then for a token.
+    pub(crate) async fn acquire(&self) -> Permit<'_> {
+        let start = Instant::now();
+        let queued = Queued::new(self);
+
+        let in_flight = match &self.in_flight {
+            Some(semaphore) => Some(
+                semaphore
+                    .acquire()
+                    .await
+                    .expect("Semaphore is never closed"),
+            ),
+            None => None,
+        };
+        if let Some(bucket) = &self.bucket {
+            let mut bucket = bucket.lock().await;
+            bucket.refill();
+            if bucket.tokens < 1.0 {
+                let wait = (1.0 - bucket.tokens) / bucket.rate;
+                tokio::time::sleep(Duration::try_from_secs_f64(wait).unwrap_or(Duration::MAX))
+                    .await;
+                bucket.refill();
+            }
+            bucket.tokens -= 1.0;
+        }
+
+        let wait = start.elapsed();
+        drop(queued);
+        let mut stats = self.stats.lock().unwrap();
+        stats.in_flight += 1;
+        stats.requests += 1;
+        stats.total_wait += wait;
+        stats.max_wait = stats.max_wait.max(wait);
+        drop(stats);
+        #[cfg(feature = "tracing")]
+        if !wait.is_zero() {
+            tracing::debug!(?wait, "waited for rate limit");
+        }
+
+        Permit {
+            limiter: self,
+            _in_flight: in_flight,
+        }
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.limiter.stats.lock().unwrap().in_flight -= 1;
+    }
+}
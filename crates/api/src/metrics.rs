@@ -0,0 +1,240 @@
+#![cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+//! Per-endpoint request metrics.
+//!
+//! A [`Metrics`] collector given to [`ClientBuilder::metrics`](crate::ClientBuilder::metrics)
+//! records, for every generated endpoint method, the number of calls, errors by
+//! [kind](crate::Error::kind), latency and response size. Read them with
+//! [`snapshot`](Metrics::snapshot), or serve [`render_prometheus`](Metrics::render_prometheus)
+//! from your own HTTP server. Every measurement is also sent to the [`metrics`] crate's
+//! global recorder, under the same names.
+//!
+//! Calls through [`Client::rq`](crate::Client::rq) rather than an endpoint method are
+//! recorded under the endpoint `rq`.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+pub const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Upper bounds of the response size histogram buckets, in bytes.
+pub const SIZE_BUCKETS: [f64; 8] = [
+    256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0,
+];
+
+/// Observations sorted into buckets with fixed upper bounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Upper bound of each bucket.
+    pub bounds: &'static [f64],
+    /// Observations in each bucket, not cumulative. One more than `bounds`, for overflow.
+    pub counts: Vec<u64>,
+    pub sum: f64,
+    pub count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len() + 1],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        let bucket = self.bounds.partition_point(|bound| *bound < value);
+        self.counts[bucket] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// Everything recorded for one endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointMetrics {
+    pub endpoint: &'static str,
+    pub requests: u64,
+    /// Failed calls, by [`Error::kind`](crate::Error::kind).
+    pub errors: BTreeMap<&'static str, u64>,
+    /// Seconds from starting a call until it returned, successful or not.
+    pub latency: Histogram,
+    /// Body size in bytes of successful responses.
+    pub response_size: Histogram,
+}
+
+impl EndpointMetrics {
+    fn new(endpoint: &'static str) -> Self {
+        Self {
+            endpoint,
+            requests: 0,
+            errors: BTreeMap::new(),
+            latency: Histogram::new(&LATENCY_BUCKETS),
+            response_size: Histogram::new(&SIZE_BUCKETS),
+        }
+    }
+}
+
+/// Collects [`EndpointMetrics`]. Clones share the same data.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    endpoints: Arc<Mutex<BTreeMap<&'static str, EndpointMetrics>>>,
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics")
+            .field("endpoints", &self.endpoints.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a call to `endpoint`: how long it took, and the size of the response body or
+    /// the kind of error.
+    pub fn record(
+        &self,
+        endpoint: &'static str,
+        elapsed: Duration,
+        outcome: Result<usize, &'static str>,
+    ) {
+        let seconds = elapsed.as_secs_f64();
+        metrics::counter!("ptv_requests_total", "endpoint" => endpoint).increment(1);
+        metrics::histogram!("ptv_request_duration_seconds", "endpoint" => endpoint).record(seconds);
+        match outcome {
+            Ok(size) => metrics::histogram!("ptv_response_size_bytes", "endpoint" => endpoint)
+                .record(size as f64),
+            Err(kind) => {
+                metrics::counter!("ptv_errors_total", "endpoint" => endpoint, "kind" => kind)
+                    .increment(1)
+            }
+        }
+
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let metrics = endpoints
+            .entry(endpoint)
+            .or_insert_with(|| EndpointMetrics::new(endpoint));
+        metrics.requests += 1;
+        metrics.latency.observe(seconds);
+        match outcome {
+            Ok(size) => metrics.response_size.observe(size as f64),
+            Err(kind) => *metrics.errors.entry(kind).or_default() += 1,
+        }
+    }
+
+    /// The metrics of every endpoint called so far, by name.
+    pub fn snapshot(&self) -> Vec<EndpointMetrics> {
+        self.endpoints.lock().unwrap().values().cloned().collect()
+    }
+
+    pub fn reset(&self) {
+        self.endpoints.lock().unwrap().clear();
+    }
+
+    /// All metrics in the Prometheus text exposition format.
+    pub fn render_prometheus(&self) -> String {
+        let endpoints = self.snapshot();
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "ptv_requests_total",
+            "counter",
+            "Calls to each endpoint.",
+        );
+        for metrics in &endpoints {
+            writeln!(
+                out,
+                "ptv_requests_total{{endpoint=\"{}\"}} {}",
+                metrics.endpoint, metrics.requests
+            )
+            .ok();
+        }
+        header(
+            &mut out,
+            "ptv_errors_total",
+            "counter",
+            "Failed calls to each endpoint, by kind of error.",
+        );
+        for metrics in &endpoints {
+            for (kind, count) in &metrics.errors {
+                writeln!(
+                    out,
+                    "ptv_errors_total{{endpoint=\"{}\",kind=\"{kind}\"}} {count}",
+                    metrics.endpoint
+                )
+                .ok();
+            }
+        }
+        header(
+            &mut out,
+            "ptv_request_duration_seconds",
+            "histogram",
+            "Time taken by calls to each endpoint.",
+        );
+        for metrics in &endpoints {
+            histogram(
+                &mut out,
+                "ptv_request_duration_seconds",
+                metrics.endpoint,
+                &metrics.latency,
+            );
+        }
+        header(
+            &mut out,
+            "ptv_response_size_bytes",
+            "histogram",
+            "Body size of successful responses from each endpoint.",
+        );
+        for metrics in &endpoints {
+            histogram(
+                &mut out,
+                "ptv_response_size_bytes",
+                metrics.endpoint,
+                &metrics.response_size,
+            );
+        }
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {name} {help}").ok();
+    writeln!(out, "# TYPE {name} {kind}").ok();
+}
+
+fn histogram(out: &mut String, name: &str, endpoint: &str, histogram: &Histogram) {
+    let mut cumulative = 0;
+    let bounds = histogram.bounds.iter().map(f64::to_string);
+    for (bound, count) in bounds.chain(["+Inf".to_string()]).zip(&histogram.counts) {
+        cumulative += count;
+        writeln!(
+            out,
+            "{name}_bucket{{endpoint=\"{endpoint}\",le=\"{bound}\"}} {cumulative}"
+        )
+        .ok();
+    }
+    writeln!(
+        out,
+        "{name}_sum{{endpoint=\"{endpoint}\"}} {}",
+        histogram.sum
+    )
+    .ok();
+    writeln!(
+        out,
+        "{name}_count{{endpoint=\"{endpoint}\"}} {}",
+        histogram.count
+    )
+    .ok();
+}
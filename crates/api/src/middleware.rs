@@ -0,0 +1,99 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Hooks around every request a [`Client`] makes.
+//!
+//! A [`Middleware`] added with [`ClientBuilder::middleware`](crate::ClientBuilder::middleware)
+//! receives each [`Request`] before the cache, retries and signing, and hands it on with
+//! [`Next::run`], or answers it itself. It sees the raw response (or error) before it is
+//! deserialized. The first middleware added is the outermost.
+//!
+//! ```
+//! use ptv::{middleware::{Middleware, Next, Request}, BoxFuture, Error, TransportResponse};
+//!
+//! /// Ask for every response to be expanded.
+//! struct ExpandAll;
+//!
+//! impl Middleware for ExpandAll {
+//!     fn handle<'a>(
+//!         &'a self,
+//!         mut request: Request,
+//!         next: Next<'a>,
+//!     ) -> BoxFuture<'a, Result<TransportResponse, Error>> {
+//!         request.set_query("expand", "All");
+//!         Box::pin(next.run(request))
+//!     }
+//! }
+//! ```
+//!
+//! With the `tower` feature, [`Client`] is also a [`tower::Service`] for [`Request`]s, so
+//! any `tower::Layer` can wrap it.
+
+use std::sync::Arc;
+
+pub use crate::Request;
+use crate::{BoxFuture, CallOptions, Client, Error, TransportResponse};
+
+/// A layer around every request a [`Client`] makes.
+pub trait Middleware: Send + Sync {
+    /// Handle `request`, usually by passing it (possibly changed) to `next`.
+    ///
+    /// Returning a response without calling `next` short-circuits the request: nothing is
+    /// sent or cached. Non-success statuses are turned into [`Error::Status`] either way.
+    fn handle<'a>(
+        &'a self,
+        request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<TransportResponse, Error>>;
+}
+
+/// The rest of the middleware chain, ending with the client sending the request.
+pub struct Next<'a> {
+    client: &'a Client,
+    middleware: &'a [Arc<dyn Middleware>],
+    options: &'a CallOptions,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(client: &'a Client, options: &'a CallOptions) -> Self {
+        Self {
+            client,
+            middleware: &client.middleware,
+            options,
+        }
+    }
+
+    /// The options the call was made with.
+    pub fn options(&self) -> &CallOptions {
+        self.options
+    }
+
+    pub async fn run(self, request: Request) -> Result<TransportResponse, Error> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => {
+                let next = Next {
+                    client: self.client,
+                    middleware: rest,
+                    options: self.options,
+                };
+                first.handle(request, next).await
+            }
+            None => self.client.fetch(&request, self.options).await,
+        }
+    }
+}
+
+#[cfg(feature = "tower")]
+impl tower::Service<Request> for Client {
+    type Response = TransportResponse;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<TransportResponse, Error>>;
+
+    fn poll_ready(&mut self, _: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    /// Run `request` through the middleware chain, without deserializing the response.
+    fn call(&mut self, request: Request) -> Self::Future {
+        let client = self.clone();
+        Box::pin(async move { client.dispatch(request, &CallOptions::default()).await })
+    }
+}
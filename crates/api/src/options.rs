@@ -0,0 +1,124 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Options for a single call.
+//!
+//! Every generated endpoint method has a `*_with_options` variant taking [`CallOptions`],
+//! which override the client's configuration for that call only:
+//!
+//! ```no_run
+//! # async fn run(client: ptv::Client) -> Result<(), ptv::Error> {
+//! use std::time::Duration;
+//! use ptv::{CallOptions, CancellationToken, core::generated_types::*};
+//!
+//! let cancel = CancellationToken::new();
+//! let options = CallOptions::new()
+//!     .timeout(Duration::from_secs(2))
+//!     .cancel_on(cancel.clone())
+//!     .bypass_cache(true);
+//! let routes = client.get_routes_with_options(GetRoutesParams::default(), options).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The deadline covers the whole call, including queueing for the rate limiter, retries and
+//! their backoff. When it passes the call fails with [`Error::Timeout`]; when the token is
+//! cancelled, with [`Error::Cancelled`]. Either way the request in flight is dropped.
+
+use std::{
+    future::{Future, pending},
+    time::{Duration, Instant},
+};
+
+pub use tokio_util::sync::CancellationToken;
+
+use crate::{Error, RetryPolicy};
+
+/// Overrides for a single call, see the [module docs](self).
+#[derive(Debug, Clone, Default)]
+pub struct CallOptions {
+    /// Fail with [`Error::Timeout`] once this instant passes.
+    pub deadline: Option<Instant>,
+    /// Fail with [`Error::Timeout`] this long after the call starts.
+    pub timeout: Option<Duration>,
+    /// Fail with [`Error::Cancelled`] once this token is cancelled.
+    pub cancel: Option<CancellationToken>,
+    /// Don't answer from the [`Cache`](crate::Cache). The fresh response is still cached.
+    pub bypass_cache: bool,
+    /// Retry with this policy instead of the client's. `Some(None)` sends the request once.
+    pub retry: Option<Option<RetryPolicy>>,
+}
+
+impl CallOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    pub fn bypass_cache(mut self, bypass: bool) -> Self {
+        self.bypass_cache = bypass;
+        self
+    }
+
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(Some(policy));
+        self
+    }
+
+    /// Send the request once, even if the client retries.
+    pub fn no_retry(mut self) -> Self {
+        self.retry = Some(None);
+        self
+    }
+
+    /// The earlier of [`deadline`](Self::deadline) and [`timeout`](Self::timeout) after `start`.
+    /// A timeout too long to represent as an [`Instant`] never passes.
+    fn deadline_from(&self, start: Instant) -> Option<Instant> {
+        let timeout = self.timeout.and_then(|timeout| start.checked_add(timeout));
+        match (self.deadline, timeout) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Run `call`, started at `start`, until it finishes, the deadline passes or the token
+    /// is cancelled.
+    pub(crate) async fn guard<T>(
+        &self,
+        start: Instant,
+        call: impl Future<Output = Result<T, Error>>,
+    ) -> Result<T, Error> {
+        let cancelled = async {
+            match &self.cancel {
+                Some(token) => token.cancelled().await,
+                None => pending().await,
+            }
+        };
+        let deadline = async {
+            match self.deadline_from(start) {
+                Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                None => pending().await,
+            }
+        };
+        tokio::select! {
+            biased;
+            _ = cancelled => Err(Error::Cancelled),
+            _ = deadline => Err(Error::Timeout {
+                elapsed: start.elapsed(),
+            }),
+            res = call => res,
+        }
+    }
+}
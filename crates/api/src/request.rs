@@ -0,0 +1,144 @@
+//! What a [`Client`](crate::Client) sends, before it is signed.
+
+use http::{HeaderMap, HeaderName, HeaderValue, Method};
+use serde::Serialize;
+
+use crate::Error;
+
+/// An outgoing request, before it is signed.
+///
+/// Only the path and query are signed; headers and a body are sent as is, the body with a
+/// JSON content type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: Method,
+    /// Path and query, e.g. `/v3/routes?route_name=Alamein`.
+    pub path: String,
+    pub headers: HeaderMap,
+    /// JSON body.
+    pub body: Option<String>,
+}
+
+impl Request {
+    /// A `GET` request for `path`.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self::with_method(Method::GET, path)
+    }
+
+    pub fn with_method(method: Method, path: impl Into<String>) -> Self {
+        Self {
+            method,
+            path: path.into(),
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    pub fn get(path: impl Into<String>) -> Self {
+        Self::new(path)
+    }
+
+    pub fn post(path: impl Into<String>) -> Self {
+        Self::with_method(Method::POST, path)
+    }
+
+    pub fn put(path: impl Into<String>) -> Self {
+        Self::with_method(Method::PUT, path)
+    }
+
+    pub fn delete(path: impl Into<String>) -> Self {
+        Self::with_method(Method::DELETE, path)
+    }
+
+    /// Send `body` serialized as JSON.
+    pub fn json<B: Serialize + ?Sized>(mut self, body: &B) -> Result<Self, Error> {
+        let body =
+            serde_json::to_string(body).map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        self.body = Some(body);
+        Ok(self)
+    }
+
+    /// Set header `name`, replacing any values it had.
+    pub fn header(mut self, name: &str, value: &str) -> Result<Self, Error> {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| Error::InvalidArgument(format!("Invalid header name {name:?}: {e}")))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| Error::InvalidArgument(format!("Invalid value for header {name}: {e}")))?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Set a header for every field of `headers`, a struct or map serialized like
+    /// [`to_query`](crate::to_query). `None` fields are skipped.
+    pub fn headers_from<T: Serialize>(mut self, headers: &T) -> Result<Self, Error> {
+        let value =
+            serde_json::to_value(headers).map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        let fields = value.as_object().ok_or_else(|| {
+            Error::InvalidArgument(format!("headers must be an object, got {value}"))
+        })?;
+        for (name, value) in fields {
+            self = match value {
+                serde_json::Value::Null => self,
+                serde_json::Value::String(value) => self.header(name, value)?,
+                value => self.header(name, &value.to_string())?,
+            };
+        }
+        Ok(self)
+    }
+
+    /// Whether the response depends on the path alone, so it may be cached or shared.
+    pub(crate) fn is_plain_get(&self) -> bool {
+        self.method == Method::GET && self.headers.is_empty() && self.body.is_none()
+    }
+
+    /// The value of query parameter `name`, as it appears in the path.
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.pairs()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Set query parameter `name`, replacing any values it had.
+    pub fn set_query(&mut self, name: &str, value: &str) {
+        self.remove_query(name);
+        let separator = if self.path.contains('?') { '&' } else { '?' };
+        let value = url_escape::encode_query(value);
+        self.path = format!("{}{separator}{name}={value}", self.path);
+    }
+
+    pub fn remove_query(&mut self, name: &str) {
+        let Some((path, _)) = self.path.split_once('?') else {
+            return;
+        };
+        let query = self
+            .pairs()
+            .filter(|(key, _)| *key != name)
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        self.path = match query.is_empty() {
+            true => path.to_string(),
+            false => format!("{path}?{query}"),
+        };
+    }
+
+    fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        let query = self.path.split_once('?').map_or("", |(_, query)| query);
+        query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+    }
+}
+
+impl From<String> for Request {
+    fn from(path: String) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<&str> for Request {
+    fn from(path: &str) -> Self {
+        Self::new(path)
+    }
+}
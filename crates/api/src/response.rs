@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use http::{HeaderMap, StatusCode};
+
+use crate::DeserializeWarning;
+
+/// A deserialized response together with what was received on the wire.
+///
+/// Returned by the `*_with_response` variant of every endpoint method, for auditing and
+/// bug reports where the typed value alone isn't enough.
+#[derive(Debug, Clone)]
+pub struct ApiResponse<T> {
+    pub value: T,
+    /// The response body exactly as received.
+    pub raw_body: String,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// Time from starting the request until the body was read, including any retries.
+    pub elapsed: Duration,
+    /// The path and query requested, without `devid` or `signature`.
+    pub request_path: String,
+    /// Values dropped from the body in [lenient](crate::ClientBuilder::lenient) mode.
+    pub warnings: Vec<DeserializeWarning>,
+}
+
+impl<T> ApiResponse<T> {
+    /// Transform the typed value, keeping the response metadata.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ApiResponse<U> {
+        ApiResponse {
+            value: f(self.value),
+            raw_body: self.raw_body,
+            status: self.status,
+            headers: self.headers,
+            elapsed: self.elapsed,
+            request_path: self.request_path,
+            warnings: self.warnings,
+        }
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
@@ -0,0 +1,143 @@
+#![cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+use http::{HeaderMap, StatusCode, header::RETRY_AFTER};
+
+use crate::Error;
+
+/// Opt-in retry behaviour for [`Client`](crate::Client), set with
+/// [`ClientBuilder::retry`](crate::ClientBuilder::retry).
+///
+/// Failed attempts are retried with exponential backoff (plus jitter), honouring the
+/// `Retry-After` header when the API sends one. Each attempt is signed afresh.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for any single delay, including one requested via `Retry-After`.
+    pub max_backoff: Duration,
+    /// Factor the delay grows by after each attempt. Must be finite and not negative.
+    pub multiplier: f64,
+    /// Randomise each delay between half and all of its computed value.
+    pub jitter: bool,
+    /// Status codes considered transient.
+    pub retry_statuses: Vec<StatusCode>,
+    /// Retry when no response was received at all (connection resets, timeouts, ...).
+    pub retry_transport_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: true,
+            retry_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_transport_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Treat `status` as transient.
+    pub fn retry_status(mut self, status: StatusCode) -> Self {
+        if !self.retry_statuses.contains(&status) {
+            self.retry_statuses.push(status);
+        }
+        self
+    }
+
+    /// Stop treating `status` as transient.
+    pub fn no_retry_status(mut self, status: StatusCode) -> Self {
+        self.retry_statuses.retain(|s| *s != status);
+        self
+    }
+
+    pub fn retry_transport_errors(mut self, retry: bool) -> Self {
+        self.retry_transport_errors = retry;
+        self
+    }
+
+    /// Whether `error` is worth another attempt.
+    pub fn is_retryable(&self, error: &Error) -> bool {
+        match error {
+            Error::Transport(_) => self.retry_transport_errors,
+            Error::Status { status, .. } => self.retry_statuses.contains(status),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        if !(self.multiplier.is_finite() && self.multiplier >= 0.0) {
+            return Err(Error::Config(format!(
+                "Retry multiplier must be finite and not negative, got {}",
+                self.multiplier
+            )));
+        }
+        Ok(())
+    }
+
+    /// How long to wait after failed attempt number `attempt` (starting at 1).
+    pub fn delay(&self, attempt: u32, headers: Option<&HeaderMap>) -> Duration {
+        if let Some(retry_after) = headers.and_then(retry_after) {
+            return retry_after.min(self.max_backoff);
+        }
+        // Computed in `f64`, so a long series of retries saturates at `max_backoff`.
+        let backoff = self.initial_backoff.as_secs_f64()
+            * self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        let backoff = Duration::try_from_secs_f64(backoff)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        if self.jitter {
+            backoff.mul_f64(0.5 + fastrand::f64() / 2.0)
+        } else {
+            backoff
+        }
+    }
+}
+
+/// Parse a `Retry-After` header, given either as delay seconds or an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+        .or(Some(Duration::ZERO))
+}
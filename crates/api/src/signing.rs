@@ -0,0 +1,151 @@
+//! Request signing, as described in the PTV API documentation.
+//!
+//! A request is signed by appending the developer ID as a `devid` query parameter and then
+//! the uppercase hex HMAC-SHA1 of the resulting path and query, keyed with the developer key,
+//! as `signature`. The path is signed byte-for-byte as given: percent-encoded segments are not
+//! decoded or re-encoded and query parameters are neither sorted nor deduplicated, so a URL
+//! verifies exactly when it reaches the server the way it was signed.
+//!
+//! ```
+//! let signed = ptv::signing::sign_path("1000000", "key", "/v3/routes?route_types=0");
+//! let url = signed.to_url(ptv::API_URL);
+//! assert!(url.starts_with("https://timetableapi.ptv.vic.gov.au/v3/routes?route_types=0&devid=1000000&signature="));
+//! assert!(ptv::signing::verify("key", &url));
+//! ```
+
+use std::fmt;
+
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+type PtvHmac = Hmac<Sha1>;
+
+/// A path and query carrying `devid` and `signature` parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedUrl {
+    /// Path and query, including `devid`, that the signature was computed over.
+    path: String,
+    signature: String,
+}
+
+impl SignedUrl {
+    /// The signed path and query, including `devid` but not `signature`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The uppercase hex HMAC-SHA1 of [`path`](Self::path).
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+
+    /// The full URL under `base_url`, e.g. [`API_URL`](crate::API_URL).
+    pub fn to_url(&self, base_url: &str) -> String {
+        format!("{}{self}", base_url.trim_end_matches('/'))
+    }
+}
+
+impl fmt::Display for SignedUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}&signature={}", self.path, self.signature)
+    }
+}
+
+/// Sign `path` (with or without a query string) for `devid` with `key`.
+///
+/// Any `devid` or `signature` parameters already in `path` are replaced, so re-signing
+/// a signed path is safe. Every other parameter is kept in its original order.
+pub fn sign_path(devid: &str, key: &str, path: &str) -> SignedUrl {
+    let path = if path.starts_with('/') {
+        without_credentials(path)
+    } else {
+        without_credentials(&format!("/{path}"))
+    };
+    let separator = if path.contains('?') { "&" } else { "?" };
+    let path = format!("{path}{separator}devid={devid}");
+    let signature = signature(key, &path);
+    SignedUrl { path, signature }
+}
+
+/// Whether `url` (a full URL, or just its path and query) carries a valid signature for `key`.
+///
+/// This does not check which developer ID the URL was signed for, see [`devid`].
+pub fn verify(key: &str, url: &str) -> bool {
+    let Some((path, signature)) = path_and_query(url).rsplit_once("&signature=") else {
+        return false;
+    };
+    if signature.contains('&') || !path.contains('?') {
+        return false;
+    }
+    let mut hasher = hasher(key);
+    hasher.update(path.as_bytes());
+    hex::decode(signature).is_ok_and(|signature| hasher.verify_slice(&signature).is_ok())
+}
+
+/// The `devid` parameter of a signed URL.
+pub fn devid(url: &str) -> Option<&str> {
+    path_and_query(url)
+        .split_once('?')?
+        .1
+        .split('&')
+        .find_map(|param| param.strip_prefix("devid="))
+}
+
+/// Replace the `devid` and `signature` values in a signed URL, so it can be logged.
+pub fn redact(url: &str) -> String {
+    let Some((path, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let query = query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some((key @ ("devid" | "signature"), _)) => format!("{key}=REDACTED"),
+            _ => param.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{path}?{query}")
+}
+
+/// Strip the scheme, host, `devid` and `signature` from a signed URL.
+pub fn unsigned_path(url: &str) -> String {
+    without_credentials(path_and_query(url))
+}
+
+fn hasher(key: &str) -> PtvHmac {
+    PtvHmac::new_from_slice(key.as_bytes()).expect("HMAC accepts keys of any length")
+}
+
+fn signature(key: &str, path: &str) -> String {
+    let mut hasher = hasher(key);
+    hasher.update(path.as_bytes());
+    hex::encode(hasher.finalize().into_bytes()).to_uppercase()
+}
+
+/// Strip the scheme and host from `url`, if it has them.
+fn path_and_query(url: &str) -> &str {
+    match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |i| &rest[i..]),
+        None => url,
+    }
+}
+
+/// `path` without any `devid` or `signature` parameters, or empty parameters.
+fn without_credentials(path: &str) -> String {
+    let Some((path, query)) = path.split_once('?') else {
+        return path.to_string();
+    };
+    let query = query
+        .split('&')
+        .filter(|param| {
+            let key = param.split('=').next().unwrap_or_default();
+            !param.is_empty() && key != "devid" && key != "signature"
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    if query.is_empty() {
+        path.to_string()
+    } else {
+        format!("{path}?{query}")
+    }
+}
@@ -0,0 +1,138 @@
+//! Parsing the Swagger 2.0 document the client is generated from.
+//!
+//! The client itself is generated at compile time; [`Spec`] is for tools that look at the
+//! document at runtime, like [`drift`](crate::drift) detection and the mock server.
+
+use serde_json::Value;
+
+/// The `v3` swagger file bundled with this repository.
+pub const V3_SPEC: &str = include_str!("../../../v3");
+
+pub struct Spec {
+    document: Value,
+    routes: Vec<Route>,
+}
+
+struct Route {
+    template: String,
+    segments: Vec<Vec<Piece>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Literal(String),
+    Param,
+}
+
+impl Spec {
+    pub fn parse(json: &str) -> serde_json::Result<Self> {
+        Ok(Self::new(serde_json::from_str(json)?))
+    }
+
+    /// The bundled [`V3_SPEC`].
+    pub fn v3() -> Self {
+        Self::parse(V3_SPEC).expect("Bundled v3 spec is valid JSON")
+    }
+
+    pub fn new(document: Value) -> Self {
+        let routes = document["paths"]
+            .as_object()
+            .map(|paths| {
+                paths
+                    .iter()
+                    .filter(|(_, item)| item.get("get").is_some())
+                    .map(|(template, _)| Route {
+                        template: template.clone(),
+                        segments: split(template).map(pieces).collect(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { document, routes }
+    }
+
+    /// Find the path template matching a request path (without query string).
+    ///
+    /// Templates with more literal text win, so `/v3/routes/types` is preferred over
+    /// `/v3/routes/{route_id}`.
+    pub fn find(&self, path: &str) -> Option<&str> {
+        let segments = split(path).collect::<Vec<_>>();
+        self.routes
+            .iter()
+            .filter(|route| {
+                route.segments.len() == segments.len()
+                    && route
+                        .segments
+                        .iter()
+                        .zip(&segments)
+                        .all(|(pieces, segment)| matches(pieces, segment))
+            })
+            .max_by_key(|route| {
+                route
+                    .segments
+                    .iter()
+                    .flatten()
+                    .filter(|piece| matches!(piece, Piece::Literal(_)))
+                    .count()
+            })
+            .map(|route| route.template.as_str())
+    }
+
+    pub fn document(&self) -> &Value {
+        &self.document
+    }
+
+    /// The schema of the `200` response of the `GET` operation at `template`.
+    pub fn response_schema(&self, template: &str) -> Option<&Value> {
+        self.document["paths"][template]["get"]["responses"]["200"].get("schema")
+    }
+
+    /// Follow `schema`'s `$ref`, if it has one. Unresolvable references resolve to `null`.
+    pub fn resolve<'a>(&'a self, schema: &'a Value) -> &'a Value {
+        match schema.get("$ref").and_then(Value::as_str) {
+            Some(reference) => reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.document.pointer(pointer))
+                .unwrap_or(&Value::Null),
+            None => schema,
+        }
+    }
+}
+
+fn split(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+fn pieces(segment: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut rest = segment;
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            pieces.push(Piece::Literal(rest[..start].to_string()));
+        }
+        let end = rest[start..]
+            .find('}')
+            .map_or(rest.len(), |end| start + end + 1);
+        pieces.push(Piece::Param);
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Literal(rest.to_string()));
+    }
+    pieces
+}
+
+fn matches(pieces: &[Piece], segment: &str) -> bool {
+    match pieces {
+        [] => segment.is_empty(),
+        [Piece::Literal(literal), rest @ ..] => segment
+            .strip_prefix(literal.as_str())
+            .is_some_and(|segment| matches(rest, segment)),
+        [Piece::Param] => !segment.is_empty(),
+        [Piece::Param, Piece::Literal(literal), rest @ ..] => segment
+            .match_indices(literal.as_str())
+            .filter(|(i, _)| *i > 0)
+            .any(|(i, _)| matches(rest, &segment[i + literal.len()..])),
+        [Piece::Param, Piece::Param, ..] => false,
+    }
+}
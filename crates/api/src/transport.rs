@@ -0,0 +1,257 @@
+//! The HTTP layer underneath [`Client::rq`](crate::Client::rq).
+//!
+//! Everything the client needs from the network goes through the [`Transport`] trait,
+//! so the default [`ReqwestTransport`] can be swapped for a custom connector, a proxy
+//! stack, or the [`MemoryTransport`] used to test generated methods without a developer key.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use http::{HeaderMap, Method, StatusCode};
+
+use crate::{Request, TransportError};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The raw response handed back by a [`Transport`].
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl TransportResponse {
+    pub fn new(status: StatusCode, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+}
+
+/// Sends a fully signed request URL and returns the response.
+pub trait Transport: Send + Sync {
+    /// Send a `GET` request for `url`.
+    fn send<'a>(&'a self, url: &'a str)
+    -> BoxFuture<'a, Result<TransportResponse, TransportError>>;
+
+    /// Send `request`, signed as `url`, with its method, headers and body.
+    ///
+    /// Transports that only implement [`send`](Self::send) fail every request but a `GET`
+    /// without headers or a body.
+    fn send_request<'a>(
+        &'a self,
+        request: &'a Request,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        if request.is_plain_get() {
+            return self.send(url);
+        }
+        Box::pin(async move {
+            Err(format!(
+                "This transport can't send {} requests with headers or a body",
+                request.method
+            )
+            .into())
+        })
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        (**self).send(url)
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        request: &'a Request,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        (**self).send_request(request, url)
+    }
+}
+
+/// The default transport, backed by [`reqwest`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Transport for ReqwestTransport {
+    fn send<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        Box::pin(self.execute(self.client.get(url)))
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        request: &'a Request,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        let mut builder = self
+            .client
+            .request(request.method.clone(), url)
+            .headers(request.headers.clone());
+        if let Some(body) = &request.body {
+            builder = builder
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(body.clone());
+        }
+        Box::pin(self.execute(builder))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ReqwestTransport {
+    async fn execute(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<TransportResponse, TransportError> {
+        let res = request.send().await?;
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = res.text().await?;
+        Ok(TransportResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// An in-memory transport serving canned responses, for tests.
+///
+/// Responses are matched on the URL path alone (no host, no query string, any method), so
+/// callers don't need to know the devid or compute signatures. Several responses
+/// registered for the same path are served in order, the last one repeating.
+#[derive(Debug, Default)]
+pub struct MemoryTransport {
+    responses: Mutex<HashMap<String, VecDeque<TransportResponse>>>,
+    requests: Mutex<Vec<SentRequest>>,
+}
+
+/// A request received by a [`MemoryTransport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Option<String>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `body` with a `200 OK` for requests to `path`.
+    pub fn with_json(self, path: impl Into<String>, body: impl Into<String>) -> Self {
+        self.with_response(path, TransportResponse::new(StatusCode::OK, body))
+    }
+
+    /// Serve `response` for requests to `path`.
+    pub fn with_response(self, path: impl Into<String>, response: TransportResponse) -> Self {
+        self.insert(path, response);
+        self
+    }
+
+    pub fn insert(&self, path: impl Into<String>, response: TransportResponse) {
+        self.responses
+            .lock()
+            .unwrap()
+            .entry(path.into())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Every URL sent through this transport, in order.
+    pub fn requests(&self) -> Vec<String> {
+        self.sent().into_iter().map(|request| request.url).collect()
+    }
+
+    /// Every request sent through this transport, in order.
+    pub fn sent(&self) -> Vec<SentRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        Box::pin(async move { self.respond(Method::GET, url, HeaderMap::new(), None) })
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        request: &'a Request,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            self.respond(
+                request.method.clone(),
+                url,
+                request.headers.clone(),
+                request.body.clone(),
+            )
+        })
+    }
+}
+
+impl MemoryTransport {
+    fn respond(
+        &self,
+        method: Method,
+        url: &str,
+        headers: HeaderMap,
+        body: Option<String>,
+    ) -> Result<TransportResponse, TransportError> {
+        self.requests.lock().unwrap().push(SentRequest {
+            method,
+            url: url.to_string(),
+            headers,
+            body,
+        });
+        let path = url_path(url);
+        let mut responses = self.responses.lock().unwrap();
+        let queue = responses
+            .get_mut(path)
+            .ok_or_else(|| format!("No response registered for {path}"))?;
+        if queue.len() > 1 {
+            Ok(queue.pop_front().unwrap())
+        } else {
+            queue
+                .front()
+                .cloned()
+                .ok_or_else(|| format!("No response registered for {path}").into())
+        }
+    }
+}
+
+/// Strips the scheme, host and query string from `url`.
+fn url_path(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let path = rest.find('/').map(|i| &rest[i..]).unwrap_or("/");
+    path.split('?').next().unwrap_or(path)
+}
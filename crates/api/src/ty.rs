@@ -8,11 +8,9 @@ use chrono::NaiveDate;
 use derive_more::{Display, From};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::str::FromStr;
-use to_and_fro::ToAndFro;
 
-use crate::helpers::deserialize_path;
+use crate::{Error, helpers::deserialize_path};
 
 pub struct I32ButSilly(pub i32);
 impl<'de> Deserialize<'de> for I32ButSilly {
@@ -39,6 +37,66 @@ macro_rules! newtype_i32 {
         pub struct $name(pub i32);
     };
 }
+/// A fieldless enum with a string form for `Display` and `FromStr`.
+macro_rules! str_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $str:literal),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub enum $name {
+            $($variant),*
+        }
+
+        impl $name {
+            pub fn list() -> [$name; [$($str),*].len()] {
+                [$($name::$variant),*]
+            }
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $str),*
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($str => Ok($name::$variant),)*
+                    _ => Err(Error::InvalidArgument(format!(
+                        "Invalid variant {} for enum {}",
+                        s,
+                        stringify!($name)
+                    ))),
+                }
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = Error;
+
+            fn try_from(s: &str) -> Result<Self, Self::Error> {
+                s.parse()
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = Error;
+
+            fn try_from(s: String) -> Result<Self, Self::Error> {
+                s.parse()
+            }
+        }
+    };
+}
+
 newtype_i32!(DisruptionId);
 
 newtype_i32!(RunId);
@@ -165,7 +223,7 @@ impl DisruptionMode {
 
 //
 
-#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Status {
     /// API Version number
     pub version: String,
@@ -174,32 +232,31 @@ pub struct Status {
 }
 
 //
-#[derive(ToAndFro, PartialOrd, Ord, Serialize, Deserialize)]
-#[input_case("lower")]
-#[output_case("lower")]
-pub enum DisruptionStatus {
-    Current,
-    Planned,
-}
+str_enum!(
+    #[derive(PartialOrd, Ord)]
+    DisruptionStatus {
+        Current => "current",
+        Planned => "planned",
+    }
+);
 
-#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ApiError {
     pub message: String,
     pub status: Status,
 }
 
-#[derive(ToAndFro, Serialize, Deserialize)]
-pub enum ExpandOptions {
-    All,
-    Stop,
-    Route,
-    Run,
-    Direction,
-    Disruption,
-    VehiclePosition,
-    VehicleDescriptor,
-    None,
-}
+str_enum!(ExpandOptions {
+    All => "All",
+    Stop => "Stop",
+    Route => "Route",
+    Run => "Run",
+    Direction => "Direction",
+    Disruption => "Disruption",
+    VehiclePosition => "VehiclePosition",
+    VehicleDescriptor => "VehicleDescriptor",
+    None => "None",
+});
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "camelCase")]
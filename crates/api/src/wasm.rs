@@ -0,0 +1,215 @@
+#![cfg(target_arch = "wasm32")]
+//! The [`Client`] in the browser.
+//!
+//! On `wasm32` requests are sent with the `fetch` API of the window or worker the module
+//! runs in, and every generated endpoint method works as it does natively. Retries, caching,
+//! rate limiting and the other [`ClientBuilder`](crate::ClientBuilder) options are not
+//! available.
+//!
+//! Requests can be signed in the browser with [`Client::new`], but that ships the developer
+//! key to every visitor. [`Client::with_signing_endpoint`] keeps the key on a server instead:
+//! for each request the client calls the endpoint with the unsigned path and query in a
+//! `path` query parameter, e.g. `GET /sign?path=%2Fv3%2Froutes%3Froute_types%3D0`, and
+//! expects the signed path and query as the plain text body. A server can produce it with
+//! [`signing::sign_path`]:
+//!
+//! ```
+//! let body = ptv::signing::sign_path("1000000", "key", "/v3/routes?route_types=0").to_string();
+//! assert!(body.starts_with("/v3/routes?route_types=0&devid=1000000&signature="));
+//! ```
+
+use std::{fmt::Debug, time::Duration};
+
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use serde::de::DeserializeOwned;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    API_URL, ApiResponse, Client, Credentials, Error, Request, TransportError, TransportResponse,
+    core::check_status, signing,
+};
+
+/// How a browser [`Client`] signs its requests.
+#[derive(Debug, Clone)]
+pub enum Signer {
+    /// Sign in the browser with the developer key.
+    Local(Credentials),
+    /// Ask a server to sign each request, see the [module docs](self).
+    Remote { endpoint: String },
+}
+
+impl Signer {
+    /// The signed path and query for `path`, without the base URL.
+    pub async fn sign(&self, transport: &FetchTransport, path: &str) -> Result<String, Error> {
+        match self {
+            Signer::Local(credentials) => {
+                Ok(signing::sign_path(credentials.devid(), credentials.key(), path).to_string())
+            }
+            Signer::Remote { endpoint } => {
+                let separator = if endpoint.contains('?') { '&' } else { '?' };
+                let url = format!(
+                    "{endpoint}{separator}path={}",
+                    url_escape::encode_component(path)
+                );
+                let res = check_status(transport.send(&url).await?)?;
+                let signed = res.body.trim();
+                if !signed.starts_with('/') {
+                    return Err(Error::Transport(
+                        format!("Signing endpoint returned {signed:?}, not a signed path").into(),
+                    ));
+                }
+                Ok(signed.to_string())
+            }
+        }
+    }
+}
+
+/// Sends requests with the `fetch` API of the current window or worker.
+#[derive(Debug, Clone, Default)]
+pub struct FetchTransport;
+
+impl FetchTransport {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Send a `GET` request for `url`.
+    pub async fn send(&self, url: &str) -> Result<TransportResponse, TransportError> {
+        self.send_request(&Request::new(url), url).await
+    }
+
+    /// Send `request`, signed as `url`, with its method, headers and body.
+    pub async fn send_request(
+        &self,
+        request: &Request,
+        url: &str,
+    ) -> Result<TransportResponse, TransportError> {
+        fetch(request, url).await.map_err(|e| match e.as_string() {
+            Some(message) => message.into(),
+            None => format!("{e:?}").into(),
+        })
+    }
+}
+
+async fn fetch(request: &Request, url: &str) -> Result<TransportResponse, JsValue> {
+    let init = web_sys::RequestInit::new();
+    init.set_method(request.method.as_str());
+    let headers = web_sys::Headers::new()?;
+    for (name, value) in &request.headers {
+        let value = value
+            .to_str()
+            .map_err(|_| JsValue::from_str(&format!("Invalid value for header {name}")))?;
+        headers.append(name.as_str(), value)?;
+    }
+    if let Some(body) = &request.body {
+        headers.set("Content-Type", "application/json")?;
+        init.set_body(&JsValue::from_str(body));
+    }
+    init.set_headers(&headers);
+    let global = js_sys::global();
+    let promise = if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+        window.fetch_with_str_and_init(url, &init)
+    } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+        worker.fetch_with_str_and_init(url, &init)
+    } else {
+        return Err(JsValue::from_str("fetch is not available in this context"));
+    };
+    let res: web_sys::Response = JsFuture::from(promise).await?.dyn_into()?;
+
+    let mut headers = HeaderMap::new();
+    if let Some(entries) = js_sys::try_iter(&res.headers())? {
+        for entry in entries {
+            let entry: js_sys::Array = entry?.dyn_into()?;
+            let (Some(name), Some(value)) = (entry.get(0).as_string(), entry.get(1).as_string())
+            else {
+                continue;
+            };
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+    }
+    let status = StatusCode::from_u16(res.status())
+        .map_err(|_| JsValue::from_str(&format!("Invalid status {}", res.status())))?;
+    let body = JsFuture::from(res.text()?)
+        .await?
+        .as_string()
+        .unwrap_or_default();
+    Ok(TransportResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+impl Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("signer", &self.signer)
+            .field("base_url", &self.base_url)
+            .field("lenient", &self.lenient)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// A client signing requests in the browser. This exposes the developer key to anyone
+    /// who can load the page; prefer [`with_signing_endpoint`](Self::with_signing_endpoint).
+    pub fn new(devid: String, token: String) -> Self {
+        Self::with_signer(Signer::Local(Credentials::new(devid, token)))
+    }
+
+    /// A client having its requests signed by the server at `endpoint`, see the
+    /// [module docs](crate::wasm).
+    pub fn with_signing_endpoint(endpoint: impl Into<String>) -> Self {
+        Self::with_signer(Signer::Remote {
+            endpoint: endpoint.into(),
+        })
+    }
+
+    pub fn with_signer(signer: Signer) -> Self {
+        Self {
+            signer,
+            base_url: API_URL.to_string(),
+            transport: FetchTransport::new(),
+            lenient: false,
+        }
+    }
+
+    /// Send requests to `base_url` instead of [`API_URL`].
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// See [`lenient`](crate::lenient).
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    pub fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    /// Like [`rq_response`](Self::rq_response). There are no metrics on `wasm32`, so
+    /// `endpoint` is unused.
+    pub async fn rq_response_named<T: DeserializeOwned + Debug>(
+        &self,
+        _endpoint: &'static str,
+        request: impl Into<Request>,
+    ) -> Result<ApiResponse<T>, Error> {
+        let request = request.into();
+        let start = js_sys::Date::now();
+        let signed = self.signer.sign(&self.transport, &request.path).await?;
+        let url = format!("{}{signed}", self.base_url.trim_end_matches('/'));
+        let res = self.transport.send_request(&request, &url).await?;
+        let res = check_status(res)?;
+        let elapsed = Duration::from_secs_f64((js_sys::Date::now() - start).max(0.0) / 1000.0);
+        self.deserialize_response(res, elapsed, request.path)
+    }
+}
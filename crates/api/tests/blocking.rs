@@ -0,0 +1,53 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use std::sync::Arc;
+
+use ptv::{core::generated_types::*, *};
+
+#[test]
+fn mirrors_async_methods() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .with_json(
+                "/v3/directions/route/1",
+                r#"{ "directions": [ { "direction_id": 1, "direction_name": "City" } ] }"#,
+            )
+            .with_json(
+                "/v3/routes",
+                r#"{ "route": { "route_id": 1, "route_name": "Alamein" } }"#,
+            ),
+    );
+    let client = common::builder(transport.clone()).build_blocking().unwrap();
+
+    let res = client.get_directions_by_route_id(RouteId(1)).unwrap();
+    assert_eq!(
+        res.directions.unwrap()[0].direction_name.as_deref(),
+        Some("City")
+    );
+
+    let res = client
+        .get_routes(GetRoutesParams {
+            route_name: Some("Alamein".into()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(res.route.unwrap().route_name.as_deref(), Some("Alamein"));
+
+    let requests = transport.requests();
+    assert!(requests[1].contains("/v3/routes?route_name=Alamein&devid=1234&signature="));
+}
+
+#[test]
+fn reports_errors() {
+    let client = blocking::Client::from_async(Client::with_transport(
+        "1234".into(),
+        "key".into(),
+        MemoryTransport::new(),
+    ))
+    .unwrap();
+
+    let err = client.get_directions_by_route_id(RouteId(1)).unwrap_err();
+    assert!(matches!(err, Error::Transport(_)));
+}
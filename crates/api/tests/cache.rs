@@ -0,0 +1,124 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use ptv::*;
+
+const DIRECTIONS: &str = r#"{ "directions": [] }"#;
+const DEPARTURES: &str = r#"{ "departures": [] }"#;
+
+fn client(transport: Arc<MemoryTransport>, cache: Cache) -> Client {
+    common::builder(transport).cache(cache).build().unwrap()
+}
+
+#[tokio::test]
+async fn serves_repeated_requests_from_cache() {
+    let transport =
+        Arc::new(MemoryTransport::new().with_json("/v3/directions/route/1", DIRECTIONS));
+    let client = client(transport.clone(), Cache::memory(16));
+
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert_eq!(transport.requests().len(), 1);
+
+    client.cache().unwrap().invalidate_prefix("/v3/directions");
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn ttl_is_per_endpoint_family() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .with_json("/v3/directions/route/1", DIRECTIONS)
+            .with_json("/v3/departures/route_type/0/stop/1002", DEPARTURES),
+    );
+    let policy = CachePolicy::default().ttl("/v3/departures", Duration::from_millis(20));
+    let client = client(transport.clone(), Cache::memory(16).policy(policy));
+
+    for _ in 0..2 {
+        client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+        client
+            .get_departures_by_route_type_and_stop_id(
+                RouteType::Train,
+                StopId(1002),
+                Default::default(),
+            )
+            .await
+            .unwrap();
+    }
+    assert_eq!(transport.requests().len(), 2);
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    client
+        .get_departures_by_route_type_and_stop_id(
+            RouteType::Train,
+            StopId(1002),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[test]
+fn policy_matches_on_segment_boundaries() {
+    let policy = CachePolicy::default();
+    assert_eq!(
+        policy.ttl_for("/v3/routes?route_types=0"),
+        Some(Duration::from_secs(60 * 60))
+    );
+    assert_eq!(
+        policy.ttl_for("/v3/route_types"),
+        Some(Duration::from_secs(24 * 60 * 60))
+    );
+    assert_eq!(policy.ttl_for("/v3/runs/1"), None);
+}
+
+#[test]
+fn memory_cache_evicts_least_recently_used() {
+    let cache = MemoryCache::new(2);
+    let ttl = Duration::from_secs(60);
+    cache.put("a", "1", ttl);
+    cache.put("b", "2", ttl);
+    assert_eq!(cache.get("a").as_deref(), Some("1"));
+    cache.put("c", "3", ttl);
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get("b"), None);
+    assert_eq!(cache.get("a").as_deref(), Some("1"));
+    assert_eq!(cache.get("c").as_deref(), Some("3"));
+}
+
+#[test]
+fn disk_cache_round_trips() {
+    let dir = std::env::temp_dir().join(format!("ptv-cache-{}", std::process::id()));
+    let cache = DiskCache::new(&dir).unwrap();
+    let ttl = Duration::from_secs(60);
+
+    cache.put("/v3/routes/1", "route", ttl);
+    cache.put("/v3/stops/1", "stop", ttl);
+    assert_eq!(cache.get("/v3/routes/1").as_deref(), Some("route"));
+
+    cache.invalidate_prefix("/v3/routes");
+    assert_eq!(cache.get("/v3/routes/1"), None);
+    assert_eq!(cache.get("/v3/stops/1").as_deref(), Some("stop"));
+
+    cache.clear();
+    assert_eq!(cache.get("/v3/stops/1"), None);
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn huge_ttls_never_expire() {
+    let memory = MemoryCache::new(2);
+    memory.put("a", "1", Duration::MAX);
+    assert_eq!(memory.get("a").as_deref(), Some("1"));
+
+    let dir = std::env::temp_dir().join(format!("ptv-cache-max-{}", std::process::id()));
+    let disk = DiskCache::new(&dir).unwrap();
+    disk.put("/v3/routes/1", "route", Duration::MAX);
+    assert_eq!(disk.get("/v3/routes/1").as_deref(), Some("route"));
+    std::fs::remove_dir_all(dir).ok();
+}
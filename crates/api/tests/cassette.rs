@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use ptv::*;
+
+const BODY: &str = r#"{ "directions": [ { "direction_id": 1, "direction_name": "City" } ] }"#;
+
+fn fixtures(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ptv-cassette-{name}-{}", std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    dir
+}
+
+#[tokio::test]
+async fn replays_recorded_responses() {
+    let dir = fixtures("replay");
+
+    let live = Arc::new(MemoryTransport::new().with_json("/v3/directions/route/1", BODY));
+    let recorder = Client::with_transport(
+        "1234".into(),
+        "real-key".into(),
+        Cassette::record(&dir, live.clone()),
+    );
+    let recorded = recorder
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap();
+    assert_eq!(live.requests().len(), 1);
+
+    let fixture =
+        std::fs::read_to_string(Cassette::replay(&dir).fixture_file("/v3/directions/route/1"))
+            .unwrap();
+    assert!(!fixture.contains("1234") && !fixture.contains("signature"));
+
+    let replayer = Client::with_transport("0".into(), "0".into(), Cassette::replay(&dir));
+    let replayed = replayer
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap();
+    assert_eq!(format!("{recorded:?}"), format!("{replayed:?}"));
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test]
+async fn keys_fixtures_by_method_headers_and_body() {
+    let dir = fixtures("method");
+
+    let live = Arc::new(
+        MemoryTransport::new()
+            .with_json("/v3/departures/bulk", r#"{ "count": 2 }"#)
+            .with_json("/v3/departures/bulk", r#"{ "count": 3 }"#)
+            .with_json("/v3/routes", r#"{ "routes": [] }"#),
+    );
+    let requests = [
+        Request::post("/v3/departures/bulk")
+            .json(&serde_json::json!({ "stop_ids": [1071] }))
+            .unwrap(),
+        Request::post("/v3/departures/bulk")
+            .json(&serde_json::json!({ "stop_ids": [1071, 1072] }))
+            .unwrap(),
+        Request::get("/v3/routes")
+            .header("Accept-Language", "en-AU")
+            .unwrap(),
+    ];
+    let recorder = Client::with_transport(
+        "1234".into(),
+        "real-key".into(),
+        Cassette::record(&dir, live.clone()),
+    );
+    let mut recorded = Vec::new();
+    for request in &requests {
+        recorded.push(
+            recorder
+                .rq::<serde_json::Value>(request.clone())
+                .await
+                .unwrap(),
+        );
+    }
+    assert_eq!(live.sent()[0].method, http::Method::POST);
+    assert_eq!(live.sent()[2].headers["accept-language"], "en-AU");
+
+    let replayer = Client::with_transport("0".into(), "0".into(), Cassette::replay(&dir));
+    for (request, recorded) in requests.iter().zip(&recorded) {
+        let replayed = replayer
+            .rq::<serde_json::Value>(request.clone())
+            .await
+            .unwrap();
+        assert_eq!(&replayed, recorded);
+    }
+    assert_ne!(recorded[0], recorded[1], "bodies are keyed separately");
+
+    let err = replayer
+        .rq::<serde_json::Value>(Request::get("/v3/departures/bulk"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("GET /v3/departures/bulk"));
+    let err = replayer
+        .rq::<serde_json::Value>(Request::get("/v3/routes"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::Transport(_)),
+        "headers are part of the key"
+    );
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test]
+async fn replay_fails_on_unrecorded_requests() {
+    let dir = fixtures("missing");
+    let client = Client::with_transport("0".into(), "0".into(), Cassette::replay(&dir));
+
+    let err = client
+        .get_directions_by_route_id(RouteId(2))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Transport(_)));
+    assert!(err.to_string().contains("/v3/directions/route/2"));
+}
+
+#[test]
+fn unsigned_path_strips_credentials() {
+    assert_eq!(
+        unsigned_path("https://example.com/v3/routes?route_types=0&devid=1&signature=AB"),
+        "/v3/routes?route_types=0"
+    );
+    assert_eq!(
+        unsigned_path("https://example.com/v3/route_types?devid=1&signature=AB"),
+        "/v3/route_types"
+    );
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU8, Ordering},
+    },
+    time::Duration,
+};
+
+use http::StatusCode;
+use ptv::*;
+
+const DOWN: u8 = 0;
+const DEGRADED: u8 = 1;
+const UP: u8 = 2;
+
+/// Fails with `503` while down, and reports `health: 0` while degraded.
+struct FlakyTransport {
+    mode: AtomicU8,
+    paths: Mutex<Vec<String>>,
+}
+
+impl FlakyTransport {
+    fn new(mode: u8) -> Self {
+        Self {
+            mode: AtomicU8::new(mode),
+            paths: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn set(&self, mode: u8) {
+        self.mode.store(mode, Ordering::SeqCst);
+    }
+
+    fn paths(&self) -> Vec<String> {
+        std::mem::take(&mut self.paths.lock().unwrap())
+    }
+}
+
+impl Transport for FlakyTransport {
+    fn send<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            self.paths.lock().unwrap().push(signing::unsigned_path(url));
+            let health = match self.mode.load(Ordering::SeqCst) {
+                DOWN => return Ok(TransportResponse::new(StatusCode::SERVICE_UNAVAILABLE, "")),
+                DEGRADED => 0,
+                _ => 1,
+            };
+            Ok(TransportResponse::new(
+                StatusCode::OK,
+                format!(
+                    r#"{{ "directions": [], "route_types": [], "status": {{ "version": "3.0", "health": {health} }} }}"#
+                ),
+            ))
+        })
+    }
+}
+
+fn client(transport: Arc<FlakyTransport>) -> Client {
+    common::builder(transport)
+        .circuit_breaker(
+            CircuitBreakerPolicy::new()
+                .window(4, 4)
+                .failure_rate(0.5)
+                .probe_interval(Duration::from_millis(50)),
+        )
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn opens_after_failures_and_fails_fast() {
+    let transport = Arc::new(FlakyTransport::new(UP));
+    let client = client(transport.clone());
+    let breaker = client.circuit_breaker().unwrap();
+
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    transport.set(DOWN);
+    client
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    client
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert_eq!(transport.paths().len(), 4);
+
+    let err = client
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::CircuitOpen { retry_in } if retry_in > Duration::ZERO));
+    assert!(transport.paths().is_empty());
+
+    breaker.reset();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn degraded_health_counts_as_failure() {
+    let transport = Arc::new(FlakyTransport::new(DEGRADED));
+    let client = client(transport.clone());
+
+    for _ in 0..4 {
+        client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    }
+    assert_eq!(
+        client.circuit_breaker().unwrap().state(),
+        CircuitState::Open
+    );
+}
+
+#[tokio::test]
+async fn probes_before_closing() {
+    let transport = Arc::new(FlakyTransport::new(DOWN));
+    let client = client(transport.clone());
+    let breaker = client.circuit_breaker().unwrap();
+    for _ in 0..4 {
+        client
+            .get_directions_by_route_id(RouteId(1))
+            .await
+            .unwrap_err();
+    }
+    transport.paths();
+
+    // Still degraded: the probe fails and the breaker stays open.
+    transport.set(DEGRADED);
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let err = client
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::CircuitOpen { .. }));
+    assert_eq!(transport.paths(), ["/v3/route_types"]);
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    transport.set(UP);
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert_eq!(
+        transport.paths(),
+        ["/v3/route_types", "/v3/directions/route/1"]
+    );
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[test]
+fn rejects_invalid_policies() {
+    for policy in [
+        CircuitBreakerPolicy::new().window(0, 0),
+        CircuitBreakerPolicy::new().window(10, 0),
+        CircuitBreakerPolicy::new().window(5, 10),
+        CircuitBreakerPolicy::new().failure_rate(0.0),
+        CircuitBreakerPolicy::new().failure_rate(1.5),
+    ] {
+        let err = Client::builder("1234", "key")
+            .circuit_breaker(policy)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, Error::Config(_)));
+    }
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use common::SlowTransport;
+use futures::future::join_all;
+use http::StatusCode;
+use ptv::*;
+
+fn client(transport: Arc<SlowTransport>) -> Client {
+    common::builder(transport).coalesce(true).build().unwrap()
+}
+
+fn transport() -> Arc<SlowTransport> {
+    Arc::new(SlowTransport::new(Duration::from_millis(20)).with_response(
+        "/v3/directions/route/0",
+        TransportResponse::new(
+            StatusCode::NOT_FOUND,
+            r#"{ "message": "No route", "status": { "version": "3.0", "health": 1 } }"#,
+        ),
+    ))
+}
+
+#[tokio::test]
+async fn identical_calls_share_a_request() {
+    let transport = transport();
+    let client = client(transport.clone());
+
+    let calls = [1, 1, 2, 1, 2].map(|id| client.get_directions_by_route_id(RouteId(id)));
+    let results = join_all(calls).await;
+    for res in results {
+        assert_eq!(res.unwrap().directions.unwrap()[0].direction_id, Some(1));
+    }
+    assert_eq!(
+        transport.paths(),
+        ["/v3/directions/route/1", "/v3/directions/route/2"]
+    );
+
+    // Calls that don't overlap are sent separately.
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert_eq!(transport.paths().len(), 3);
+}
+
+#[tokio::test]
+async fn errors_are_shared() {
+    let transport = transport();
+    let client = client(transport.clone());
+
+    let results = join_all((0..3).map(|_| client.get_directions_by_route_id(RouteId(0)))).await;
+    for res in results {
+        let err = res.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+        assert!(err.to_string().contains("No route"), "{err}");
+    }
+    assert_eq!(transport.paths().len(), 1);
+}
+
+#[tokio::test]
+async fn followers_take_over_from_a_cancelled_call() {
+    let transport = transport();
+    let client = client(transport.clone());
+
+    let leader = tokio::time::timeout(
+        Duration::from_millis(5),
+        client.get_directions_by_route_id(RouteId(1)),
+    );
+    let follower = async {
+        tokio::task::yield_now().await;
+        client.get_directions_by_route_id(RouteId(1)).await
+    };
+    let (leader, follower) = tokio::join!(leader, follower);
+    assert!(leader.is_err());
+    assert!(follower.is_ok());
+    assert_eq!(transport.paths().len(), 2);
+}
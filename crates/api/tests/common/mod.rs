@@ -0,0 +1,71 @@
+//! Scaffolding shared by the integration tests.
+#![allow(dead_code)]
+
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use http::StatusCode;
+use ptv::*;
+
+/// A builder for a client with test credentials, sending everything through `transport`.
+pub fn builder(transport: impl Transport + 'static) -> ClientBuilder {
+    Client::builder("1234", "key").transport(transport)
+}
+
+/// Answers every request after `delay`, recording the unsigned paths it was sent and the
+/// peak number of requests in flight.
+#[derive(Default)]
+pub struct SlowTransport {
+    delay: Duration,
+    responses: HashMap<String, TransportResponse>,
+    // (in flight, peak, paths)
+    state: Mutex<(usize, usize, Vec<String>)>,
+}
+
+impl SlowTransport {
+    /// Answer with one direction and a `200 OK`, unless a path has its own response.
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            ..Self::default()
+        }
+    }
+
+    /// Serve `response` for requests to `path`.
+    pub fn with_response(mut self, path: impl Into<String>, response: TransportResponse) -> Self {
+        self.responses.insert(path.into(), response);
+        self
+    }
+
+    pub fn peak(&self) -> usize {
+        self.state.lock().unwrap().1
+    }
+
+    pub fn paths(&self) -> Vec<String> {
+        self.state.lock().unwrap().2.clone()
+    }
+}
+
+impl Transport for SlowTransport {
+    fn send<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            let path = signing::unsigned_path(url);
+            {
+                let mut state = self.state.lock().unwrap();
+                state.0 += 1;
+                state.1 = state.1.max(state.0);
+                state.2.push(path.clone());
+            }
+            tokio::time::sleep(self.delay).await;
+            self.state.lock().unwrap().0 -= 1;
+            Ok(self.responses.get(&path).cloned().unwrap_or_else(|| {
+                TransportResponse::new(
+                    StatusCode::OK,
+                    r#"{ "directions": [{ "direction_id": 1 }] }"#,
+                )
+            }))
+        })
+    }
+}
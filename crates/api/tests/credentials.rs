@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use ptv::*;
+
+const DEVID: &str = "3000123";
+const KEY: &str = "9c132d31-6a30-4cac-8d8b-8a1970834799";
+
+fn config_file(name: &str, contents: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("ptv-config-{name}-{}.toml", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn debug_output_is_redacted() {
+    let credentials = Credentials::new(DEVID, KEY);
+    let client = Client::builder(DEVID, KEY).build().unwrap();
+
+    for output in [
+        format!("{credentials:?}"),
+        format!("{credentials}"),
+        format!("{client:?}"),
+        format!("{client:#?}"),
+    ] {
+        assert!(!output.contains(DEVID), "{output}");
+        assert!(!output.contains(KEY), "{output}");
+        assert!(output.contains("REDACTED"), "{output}");
+    }
+    assert_eq!(client.credentials().devid(), DEVID);
+    assert_eq!(client.credentials().key(), KEY);
+}
+
+#[tokio::test]
+async fn loads_config_files() {
+    let path = config_file(
+        "full",
+        &format!(
+            "devid = \"{DEVID}\"\ntoken = \"{KEY}\"\nbase_url = \"http://localhost:8080/\"\ntimeout_secs = 5\n"
+        ),
+    );
+    let transport = Arc::new(MemoryTransport::new().with_json("/v3/directions/route/1", "{}"));
+    let client = ClientBuilder::from_config_file(&path)
+        .unwrap()
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+
+    let url = &transport.requests()[0];
+    assert!(url.starts_with(&format!(
+        "http://localhost:8080/v3/directions/route/1?devid={DEVID}&signature="
+    )));
+    assert!(signing::verify(KEY, url));
+
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn reports_bad_config_files() {
+    let err = Client::from_config_file("/nonexistent/ptv.toml").unwrap_err();
+    assert!(matches!(err, Error::Config(_)));
+
+    let path = config_file("missing-key", &format!("devid = \"{DEVID}\"\n"));
+    let err = Client::from_config_file(&path).unwrap_err();
+    assert!(matches!(err, Error::Config(_)));
+    assert!(err.to_string().contains("key"), "{err}");
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use std::sync::Arc;
+
+use ptv::{drift::*, spec::Spec, *};
+use serde_json::json;
+
+#[test]
+fn matching_responses_are_clean() {
+    let spec = Spec::v3();
+    let body = r#"{ "route": { "route_id": 1, "route_name": "Alamein", "geopath": [] }, "status": { "version": "3.0", "health": 1 } }"#;
+    let report = drift::check(&spec, "/v3/routes/1?geopath=false", body).unwrap();
+    assert!(report.is_clean(), "{report}");
+    assert_eq!(report.template, "/v3/routes/{route_id}");
+}
+
+#[test]
+fn reports_extra_fields_type_mismatches_and_enum_values() {
+    let spec = Spec::v3();
+    let body = r#"{ "route": { "route_id": "1", "route_colour": "blue" }, "status": { "version": "3.0", "health": 2 } }"#;
+    let report = drift::check(&spec, "/v3/routes/1", body).unwrap();
+
+    assert_eq!(
+        report.drifts,
+        [
+            Drift {
+                path: "route.route_colour".into(),
+                definition: Some("V3.RouteWithStatus".into()),
+                kind: DriftKind::ExtraField {
+                    field: "route_colour".into(),
+                    value: json!("blue"),
+                },
+            },
+            Drift {
+                path: "route.route_id".into(),
+                definition: Some("V3.RouteWithStatus".into()),
+                kind: DriftKind::TypeMismatch {
+                    expected: "integer".into(),
+                    found: "string".into(),
+                },
+            },
+            Drift {
+                path: "status.health".into(),
+                definition: Some("V3.Status".into()),
+                kind: DriftKind::UndeclaredEnumValue {
+                    value: json!(2),
+                    allowed: vec![json!(0), json!(1)],
+                },
+            },
+        ]
+    );
+    assert_eq!(
+        report.definitions().into_iter().collect::<Vec<_>>(),
+        ["V3.RouteWithStatus", "V3.Status"]
+    );
+}
+
+#[test]
+fn reports_missing_required_fields() {
+    let spec = Spec::parse(
+        r##"{
+            "paths": { "/v3/things/{id}": { "get": { "responses": { "200": { "schema": { "$ref": "#/definitions/Thing" } } } } } },
+            "definitions": {
+                "Thing": {
+                    "type": "object",
+                    "required": ["id", "name"],
+                    "properties": { "id": { "type": "integer" }, "name": { "type": "string" } }
+                }
+            }
+        }"##,
+    )
+    .unwrap();
+    let report = drift::check(&spec, "/v3/things/1", r#"{ "id": 1 }"#).unwrap();
+    assert_eq!(report.drifts.len(), 1);
+    assert_eq!(
+        report.drifts[0].to_string(),
+        "<root> (Thing): missing required field `name`"
+    );
+
+    let err = drift::check(&spec, "/v3/unknown", "{}").unwrap_err();
+    assert!(matches!(err, Error::InvalidArgument(_)));
+}
+
+#[tokio::test]
+async fn checks_client_responses() {
+    let body = r#"{ "directions": [{ "direction_id": 1.5, "direction_name": "City" }] }"#;
+    let transport = Arc::new(MemoryTransport::new().with_json("/v3/directions/route/1", body));
+    let client = Client::with_transport("1234".into(), "key".into(), transport);
+
+    let err = client
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Deserialize { .. }));
+
+    let client = common::builder(Arc::new(
+        MemoryTransport::new().with_json("/v3/directions/route/1", body),
+    ))
+    .lenient(true)
+    .build()
+    .unwrap();
+    let res = client
+        .get_directions_by_route_id_with_response(RouteId(1))
+        .await
+        .unwrap();
+    let report = drift::check_response(&Spec::v3(), &res).unwrap();
+    assert_eq!(report.drifts.len(), 1);
+    assert_eq!(report.drifts[0].path, "directions[0].direction_id");
+}
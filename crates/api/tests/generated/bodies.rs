@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use http::{Method, StatusCode};
+use ptv::*;
+use serde_json::json;
+
+swagger_client!(
+    /// A client generated from a spec with `POST`, `PUT` and `DELETE` operations.
+    BodyClient,
+    path = "crates/api/tests/fixtures/bodies.json",
+    strip_prefix = "V3.",
+    skip = ["devid", "signature"],
+    error = "ptv::Error"
+);
+
+#[tokio::test]
+async fn posts_a_typed_body() {
+    let transport =
+        Arc::new(MemoryTransport::new().with_json("/v3/departures/bulk", r#"{ "responses": [] }"#));
+    let client = client(transport.clone());
+    let request = serde_json::from_value(json!({
+        "requests": [{ "route_type": 0, "stop_id": 1071, "max_results": 2, "route_directions": [] }]
+    }))
+    .unwrap();
+
+    client.post_departures_bulk(request).await.unwrap();
+    client
+        .post_departures_bulk(serde_json::from_value(json!({ "requests": [] })).unwrap())
+        .await
+        .unwrap();
+
+    let sent = transport.sent();
+    assert_eq!(sent.len(), 2, "POST responses aren't cached");
+    assert_eq!(sent[0].method, Method::POST);
+    assert!(signing::verify("key", &sent[0].url));
+    let body: serde_json::Value = serde_json::from_str(sent[0].body.as_deref().unwrap()).unwrap();
+    assert_eq!(body["requests"][0]["stop_id"], 1071);
+    assert_eq!(body["requests"][0]["max_results"], 2);
+}
+
+#[tokio::test]
+async fn puts_and_deletes() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .with_response(
+                "/v3/siri-downstream-subscription",
+                TransportResponse::new(
+                    StatusCode::OK,
+                    r#"{ "valid_until": "2026-01-01T00:00:00" }"#,
+                ),
+            )
+            .with_response(
+                "/v3/siri-downstream-subscription",
+                TransportResponse::new(StatusCode::NO_CONTENT, ""),
+            ),
+    );
+    let client = client(transport.clone());
+    let subscription = serde_json::from_value(json!({
+        "start_time": "2026-01-01T00:00:00",
+        "end_time": "2026-01-02T00:00:00",
+        "subscriber_ref": "subscriber",
+        "subscription_ref": "subscription",
+        "siri_format": 0,
+        "siri_version": "2.0",
+        "consumer_address": "https://example.com/siri",
+        "initial_termination_time": "2026-01-02T00:00:00",
+        "topics": []
+    }))
+    .unwrap();
+
+    client
+        .put_siri_downstream_subscription(subscription)
+        .await
+        .unwrap();
+    client
+        .delete_siri_downstream_subscription(None)
+        .await
+        .unwrap();
+
+    let sent = transport.sent();
+    assert_eq!(sent[0].method, Method::PUT);
+    assert!(sent[0].body.as_deref().unwrap().contains("\"subscriber\""));
+    assert_eq!(sent[1].method, Method::DELETE);
+    assert_eq!(sent[1].body, None);
+}
+
+#[tokio::test]
+async fn posts_are_not_retried() {
+    let transport = Arc::new(MemoryTransport::new().with_response(
+        "/v3/departures/bulk",
+        TransportResponse::new(StatusCode::SERVICE_UNAVAILABLE, "{}"),
+    ));
+    let client = client(transport.clone());
+
+    let err = client
+        .rq::<serde_json::Value>(
+            Request::post("/v3/departures/bulk")
+                .json(&json!({}))
+                .unwrap(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!(transport.requests().len(), 1);
+}
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use ptv::*;
+
+const DIRECTIONS: &str = r#"{ "directions": [] }"#;
+
+swagger_client!(
+    /// A client generated from a spec with header parameters.
+    HeaderClient,
+    path = "crates/api/tests/fixtures/headers.json",
+    strip_prefix = "V3.",
+    skip = ["devid", "signature", "Authorization"],
+    error = "ptv::Error"
+);
+
+#[tokio::test]
+async fn sends_typed_headers() {
+    let transport =
+        Arc::new(MemoryTransport::new().with_json("/v3/directions/route/1", DIRECTIONS));
+    let client = client(transport.clone());
+
+    let headers = generated_types::GetDirectionsByRouteIdHeaders {
+        accept_language: Some("en-AU".to_string()),
+        x_page_size: Some(5),
+    };
+    client.get_directions_by_route_id(1, headers).await.unwrap();
+    client
+        .get_directions_by_route_id(1, Default::default())
+        .await
+        .unwrap();
+
+    let sent = transport.sent();
+    assert_eq!(sent.len(), 2, "requests with headers aren't cached");
+    assert!(signing::verify("key", &sent[0].url));
+    assert_eq!(sent[0].headers["accept-language"], "en-AU");
+    assert_eq!(sent[0].headers["x-page-size"], "5");
+    assert!(sent[1].headers.is_empty(), "unset headers are omitted");
+}
+
+#[test]
+fn rejects_invalid_header_values() {
+    let err = Request::new("/v3/routes")
+        .header("Accept-Language", "en\nAU")
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidArgument(_)));
+}
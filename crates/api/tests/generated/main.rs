@@ -0,0 +1,47 @@
+//! Clients generated by `SwaggerClient`, one module per spec in `tests/fixtures`.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+use std::sync::Arc;
+
+use ptv::*;
+
+/// Generate a client wrapping [`Client`] from a spec, plus a `client` factory that builds it
+/// around a [`MemoryTransport`]. Each spec needs its own module for its `generated_types`.
+macro_rules! swagger_client {
+    ($(#[$meta:meta])* $name:ident, $($swagger:tt)*) => {
+        $(#[$meta])*
+        #[derive(code_generator::SwaggerClient)]
+        #[swagger($($swagger)*)]
+        struct $name {
+            client: Client,
+        }
+
+        impl std::ops::Deref for $name {
+            type Target = Client;
+
+            fn deref(&self) -> &Client {
+                &self.client
+            }
+        }
+
+        fn client(transport: std::sync::Arc<MemoryTransport>) -> $name {
+            $name {
+                client: crate::client(transport),
+            }
+        }
+    };
+}
+
+fn client(transport: Arc<MemoryTransport>) -> Client {
+    common::builder(transport)
+        .retry(RetryPolicy::new(3))
+        .cache(Cache::memory(16))
+        .build()
+        .unwrap()
+}
+
+mod bodies;
+mod headers;
+mod openapi;
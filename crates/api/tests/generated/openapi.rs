@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use http::Method;
+use ptv::*;
+
+swagger_client!(
+    /// A client generated from an OpenAPI 3.0 document.
+    OpenApiClient,
+    path = "crates/api/tests/fixtures/openapi.json",
+    skip = ["devid", "signature"],
+    error = "ptv::Error"
+);
+
+#[tokio::test]
+async fn prefixes_paths_with_the_server() {
+    let transport = Arc::new(MemoryTransport::new().with_json(
+        "/v3/directions/route/1",
+        r#"{
+            "directions": [{
+                "direction_id": 1,
+                "direction_name": "City",
+                "route_direction_description": null
+            }]
+        }"#,
+    ));
+    let client = client(transport.clone());
+
+    let res = client.get_directions_by_route_id(1).await.unwrap();
+    let directions = res.directions.unwrap();
+    assert_eq!(directions[0].direction_name, "City");
+    assert_eq!(directions[0].route_direction_description, None);
+    assert!(signing::verify("key", &transport.requests()[0]));
+}
+
+#[tokio::test]
+async fn merges_all_of_and_generates_one_of() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .with_json(
+                "/v3/stops/1071",
+                r#"{
+                    "stop": { "stop_id": 1071, "stop_name": "Flinders Street", "stop_landmark": "Clocks" },
+                    "location": { "latitude": -37.8183, "longitude": 144.9671 }
+                }"#,
+            )
+            .with_json(
+                "/v3/stops/1071",
+                r#"{ "stop": null, "location": "Under the clocks" }"#,
+            ),
+    );
+    let client = client(transport.clone());
+    let params = generated_types::GetStopByStopIdParams {
+        stop_location: Some(true),
+    };
+
+    let res = client.get_stop_by_stop_id(1071, params).await.unwrap();
+    let stop = res.stop.unwrap();
+    assert_eq!(stop.stop_id, 1071);
+    assert_eq!(stop.stop_landmark, "Clocks");
+    assert!(matches!(
+        res.location,
+        Some(generated_types::Location::Coordinates(_))
+    ));
+    assert!(transport.requests()[0].contains("stop_location=true"));
+
+    let res = client
+        .get_stop_by_stop_id(1071, Default::default())
+        .await
+        .unwrap();
+    assert!(res.stop.is_none());
+    assert!(matches!(
+        res.location,
+        Some(generated_types::Location::String(ref location)) if location == "Under the clocks"
+    ));
+}
+
+#[tokio::test]
+async fn sends_the_request_body() {
+    let transport =
+        Arc::new(MemoryTransport::new().with_json("/v3/departures/bulk", r#"{ "count": 2 }"#));
+    let client = client(transport.clone());
+
+    // Without `extras`, generated structs can be built as literals.
+    let body = generated_types::BulkDeparturesRequest {
+        stop_ids: vec![1071, 1072],
+    };
+    let res = client.post_departures_bulk(body).await.unwrap();
+    assert_eq!(res.count, Some(2));
+
+    let sent = transport.sent();
+    assert_eq!(sent[0].method, Method::POST);
+    assert_eq!(sent[0].body.as_deref(), Some(r#"{"stop_ids":[1071,1072]}"#));
+}
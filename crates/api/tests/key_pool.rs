@@ -0,0 +1,142 @@
+use std::sync::{Arc, Mutex};
+
+use http::StatusCode;
+use ptv::*;
+
+/// Answers every request, rejecting the developer IDs in `forbidden`.
+#[derive(Default)]
+struct KeyCheckingTransport {
+    forbidden: Vec<&'static str>,
+    devids: Mutex<Vec<String>>,
+}
+
+impl KeyCheckingTransport {
+    fn forbidding(forbidden: &[&'static str]) -> Self {
+        Self {
+            forbidden: forbidden.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    fn devids(&self) -> Vec<String> {
+        self.devids.lock().unwrap().clone()
+    }
+}
+
+impl Transport for KeyCheckingTransport {
+    fn send<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            let devid = signing::devid(url).unwrap_or_default().to_string();
+            self.devids.lock().unwrap().push(devid.clone());
+            Ok(if self.forbidden.contains(&devid.as_str()) {
+                TransportResponse::new(
+                    StatusCode::FORBIDDEN,
+                    r#"{ "message": "Forbidden (invalid devid)", "status": { "version": "3.0", "health": 1 } }"#,
+                )
+            } else {
+                TransportResponse::new(StatusCode::OK, r#"{ "directions": [] }"#)
+            })
+        })
+    }
+}
+
+fn pool(strategy: KeyStrategy) -> KeyPool {
+    KeyPool::new(
+        ["1", "2", "3"].map(|devid| Credentials::new(devid, format!("key-{devid}"))),
+        strategy,
+    )
+}
+
+fn client(pool: KeyPool, transport: Arc<KeyCheckingTransport>) -> Client {
+    Client::builder("0", "unused")
+        .key_pool(pool)
+        .transport(transport)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn round_robin_spreads_requests() {
+    let transport = Arc::new(KeyCheckingTransport::default());
+    let client = client(pool(KeyStrategy::RoundRobin), transport.clone());
+
+    for _ in 0..4 {
+        client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    }
+    assert_eq!(transport.devids(), ["1", "2", "3", "1"]);
+
+    let stats = client.key_pool().unwrap().stats();
+    assert_eq!(
+        stats.iter().map(|key| key.requests).collect::<Vec<_>>(),
+        [2, 1, 1]
+    );
+}
+
+#[tokio::test]
+async fn least_recently_used_skips_disabled_keys() {
+    let transport = Arc::new(KeyCheckingTransport::default());
+    let client = client(pool(KeyStrategy::LeastRecentlyUsed), transport.clone());
+    let keys = client.key_pool().unwrap();
+
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert!(keys.disable("3"));
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert!(keys.enable("3"));
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+
+    assert_eq!(transport.devids(), ["1", "2", "1", "3"]);
+    assert!(!keys.disable("4"));
+}
+
+#[tokio::test]
+async fn failover_disables_rejected_keys() {
+    let transport = Arc::new(KeyCheckingTransport::forbidding(&["1"]));
+    let client = client(pool(KeyStrategy::Failover), transport.clone());
+
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert_eq!(transport.devids(), ["1", "2", "2"]);
+
+    let stats = client.key_pool().unwrap().stats();
+    assert!(!stats[0].enabled);
+    assert_eq!(stats[0].rejections, 1);
+    assert!(stats[1].enabled);
+    assert_eq!(stats[1].requests, 2);
+}
+
+#[tokio::test]
+async fn rejections_are_reported_without_failover() {
+    let transport = Arc::new(KeyCheckingTransport::forbidding(&["1"]));
+    let client = client(pool(KeyStrategy::RoundRobin), transport.clone());
+    let keys = client.key_pool().unwrap();
+
+    let err = client
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::FORBIDDEN));
+    assert!(keys.stats()[0].enabled);
+    assert_eq!(keys.stats()[0].rejections, 1);
+
+    for devid in ["1", "2", "3"] {
+        keys.disable(devid);
+    }
+    let err = client
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Config(_)));
+}
+
+#[test]
+fn empty_pools_are_rejected() {
+    let err = Client::builder("0", "unused")
+        .key_pool(KeyPool::new([], KeyStrategy::RoundRobin))
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, Error::Config(_)));
+}
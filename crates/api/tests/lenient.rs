@@ -0,0 +1,76 @@
+mod common;
+
+use std::sync::Arc;
+
+use ptv::*;
+
+fn client(body: &'static str, lenient: bool) -> Client {
+    let transport = Arc::new(MemoryTransport::new().with_json("/v3/directions/route/1", body));
+    common::builder(transport).lenient(lenient).build().unwrap()
+}
+
+#[tokio::test]
+async fn unknown_fields_are_kept_as_extras() {
+    let body = r#"{ "directions": [{ "direction_id": 1, "direction_name": "City", "platform_hint": "2" }], "paging": 3 }"#;
+    for lenient in [false, true] {
+        let res = client(body, lenient)
+            .get_directions_by_route_id_with_response(RouteId(1))
+            .await
+            .unwrap();
+        assert!(res.warnings.is_empty());
+        assert_eq!(res.value.extras["paging"], 3);
+        let direction = &res.value.directions.as_ref().unwrap()[0];
+        assert_eq!(direction.direction_name.as_deref(), Some("City"));
+        assert_eq!(direction.extras["platform_hint"], "2");
+    }
+}
+
+#[tokio::test]
+async fn mismatched_fields_become_none() {
+    let body = r#"{ "directions": [{ "direction_id": "one", "direction_name": "City" }] }"#;
+    let err = client(body, false)
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Deserialize { .. }), "{err}");
+
+    let res = client(body, true)
+        .get_directions_by_route_id_with_response(RouteId(1))
+        .await
+        .unwrap();
+    let direction = &res.value.directions.as_ref().unwrap()[0];
+    assert_eq!(direction.direction_id, None);
+    assert_eq!(direction.direction_name.as_deref(), Some("City"));
+    assert_eq!(res.warnings.len(), 1);
+    assert_eq!(res.warnings[0].path, "directions[0].direction_id");
+    assert_eq!(res.warnings[0].value, "one");
+}
+
+#[tokio::test]
+async fn malformed_list_elements_are_dropped() {
+    let body = r#"{ "directions": [{ "direction_id": 1 }, "two", { "direction_id": 3 }, 4] }"#;
+    let res = client(body, true)
+        .get_directions_by_route_id_with_response(RouteId(1))
+        .await
+        .unwrap();
+    let ids: Vec<_> = res
+        .value
+        .directions
+        .unwrap()
+        .iter()
+        .map(|direction| direction.direction_id)
+        .collect();
+    assert_eq!(ids, [Some(1), Some(3)]);
+    let paths: Vec<_> = res.warnings.iter().map(|w| w.path.as_str()).collect();
+    assert_eq!(paths, ["directions[1]", "directions[3]"]);
+}
+
+#[test]
+fn unsalvageable_bodies_still_fail() {
+    assert!(lenient::from_str::<Vec<u32>>("[1, 2").is_err());
+    assert!(lenient::from_str::<u32>("\"one\"").is_err());
+
+    let (value, warnings) = lenient::from_str::<Vec<u32>>("[1, \"two\", 3]").unwrap();
+    assert_eq!(value, [1, 3]);
+    assert_eq!(warnings[0].path, "[1]");
+}
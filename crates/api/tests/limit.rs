@@ -0,0 +1,88 @@
+mod common;
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use common::SlowTransport;
+use futures::future::join_all;
+use ptv::*;
+
+fn client(limit: RateLimit, transport: Arc<SlowTransport>) -> Client {
+    common::builder(transport)
+        .rate_limit(limit)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn limits_requests_in_flight() {
+    let transport = Arc::new(SlowTransport::new(Duration::from_millis(20)));
+    let client = client(RateLimit::new().max_in_flight(2), transport.clone());
+
+    let results = join_all((0..8).map(|id| client.get_directions_by_route_id(RouteId(id)))).await;
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(transport.peak(), 2);
+
+    let stats = client.limiter().unwrap().stats();
+    assert_eq!(stats.requests, 8);
+    assert_eq!((stats.in_flight, stats.queued), (0, 0));
+    assert!(stats.max_wait >= Duration::from_millis(40), "{stats:?}");
+    assert!(stats.mean_wait() > Duration::ZERO);
+}
+
+#[tokio::test]
+async fn limits_requests_per_second() {
+    let transport = Arc::new(SlowTransport::new(Duration::ZERO));
+    let client = client(
+        RateLimit::new().requests_per_second(50.0).burst(2),
+        transport.clone(),
+    );
+
+    let start = Instant::now();
+    for id in 0..6 {
+        client
+            .get_directions_by_route_id(RouteId(id))
+            .await
+            .unwrap();
+    }
+    // Two requests from the burst, then one every 20ms.
+    assert!(
+        start.elapsed() >= Duration::from_millis(75),
+        "{:?}",
+        start.elapsed()
+    );
+    assert_eq!(transport.paths().len(), 6);
+}
+
+#[tokio::test]
+async fn queues_requests_in_order() {
+    let transport = Arc::new(SlowTransport::new(Duration::from_millis(2)));
+    let client = client(
+        RateLimit::new().max_in_flight(1).requests_per_second(500.0),
+        transport.clone(),
+    );
+
+    join_all((0..6).map(|id| client.get_directions_by_route_id(RouteId(id)))).await;
+    let expected: Vec<_> = (0..6)
+        .map(|id| format!("/v3/directions/route/{id}"))
+        .collect();
+    assert_eq!(transport.paths(), expected);
+}
+
+#[test]
+fn rejects_invalid_limits() {
+    for limit in [
+        RateLimit::new().max_in_flight(0),
+        RateLimit::new().requests_per_second(0.0),
+        RateLimit::new().requests_per_second(f64::NAN),
+        RateLimit::new().requests_per_second(1e-20),
+    ] {
+        let err = Client::builder("1234", "key")
+            .rate_limit(limit)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, Error::Config(_)));
+    }
+}
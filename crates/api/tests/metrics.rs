@@ -0,0 +1,94 @@
+#![cfg(feature = "metrics")]
+
+mod common;
+
+use std::sync::Arc;
+
+use http::StatusCode;
+use ptv::{core::generated_types::*, *};
+
+const BODY: &str = r#"{ "directions": [] }"#;
+
+#[tokio::test]
+async fn records_calls_per_endpoint() {
+    let transport = MemoryTransport::new()
+        .with_json("/v3/directions/route/1", BODY)
+        .with_json("/v3/directions/route/2", "[")
+        .with_response(
+            "/v3/routes",
+            TransportResponse::new(StatusCode::NOT_FOUND, "{}"),
+        );
+    let metrics = Metrics::new();
+    let client = common::builder(Arc::new(transport))
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    client
+        .get_directions_by_route_id_with_response(RouteId(1))
+        .await
+        .unwrap();
+    client
+        .get_directions_by_route_id(RouteId(2))
+        .await
+        .unwrap_err();
+    client
+        .get_routes(GetRoutesParams {
+            route_name: Some("Nowhere".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.len(), 2);
+    let directions = &snapshot[0];
+    assert_eq!(directions.endpoint, "get_directions_by_route_id");
+    assert_eq!(directions.requests, 3);
+    assert_eq!(directions.errors.get("deserialize"), Some(&1));
+    assert_eq!(directions.latency.count, 3);
+    assert_eq!(directions.response_size.count, 2);
+    assert_eq!(directions.response_size.sum, 2.0 * BODY.len() as f64);
+    assert_eq!(directions.response_size.counts[0], 2);
+
+    let routes = &snapshot[1];
+    assert_eq!(routes.endpoint, "get_routes");
+    assert_eq!(routes.errors.get("status"), Some(&1));
+    assert_eq!(routes.response_size.count, 0);
+}
+
+#[tokio::test]
+async fn renders_prometheus_text() {
+    let metrics = Metrics::new();
+    let transport = MemoryTransport::new().with_json("/v3/directions/route/1", BODY);
+    let client = common::builder(Arc::new(transport))
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    client
+        .rq::<serde_json::Value>("/v3/directions/route/1")
+        .await
+        .unwrap();
+
+    let text = metrics.render_prometheus();
+    for line in [
+        "# TYPE ptv_requests_total counter",
+        "ptv_requests_total{endpoint=\"get_directions_by_route_id\"} 1",
+        "ptv_requests_total{endpoint=\"rq\"} 1",
+        "# TYPE ptv_request_duration_seconds histogram",
+        "ptv_request_duration_seconds_bucket{endpoint=\"rq\",le=\"+Inf\"} 1",
+        "ptv_request_duration_seconds_count{endpoint=\"rq\"} 1",
+        "ptv_response_size_bytes_bucket{endpoint=\"rq\",le=\"256\"} 1",
+        "ptv_response_size_bytes_sum{endpoint=\"rq\"} 20",
+    ] {
+        assert!(
+            text.lines().any(|l| l == line),
+            "missing {line:?} in\n{text}"
+        );
+    }
+
+    metrics.reset();
+    assert!(metrics.snapshot().is_empty());
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use http::StatusCode;
+use ptv::{
+    core::generated_types::*,
+    middleware::{Next, Request},
+    *,
+};
+
+/// Records the requests and response statuses it sees, under `name`.
+struct Audit {
+    name: &'static str,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Middleware for Audit {
+    fn handle<'a>(
+        &'a self,
+        request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<TransportResponse, Error>> {
+        Box::pin(async move {
+            let log = |entry: String| self.log.lock().unwrap().push(entry);
+            log(format!("{} > {}", self.name, request.path));
+            let res = next.run(request).await;
+            match &res {
+                Ok(res) => log(format!("{} < {}", self.name, res.status.as_u16())),
+                Err(e) => log(format!("{} < {e}", self.name)),
+            }
+            res
+        })
+    }
+}
+
+/// Routes requests for route 1 to route 2.
+struct Reroute;
+
+impl Middleware for Reroute {
+    fn handle<'a>(
+        &'a self,
+        mut request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<TransportResponse, Error>> {
+        if request.path.starts_with("/v3/routes/1") {
+            request.path = request.path.replacen("/v3/routes/1", "/v3/routes/2", 1);
+            request.set_query("route_name", "Glen Waverley line");
+        }
+        Box::pin(next.run(request))
+    }
+}
+
+/// Answers every request itself.
+struct Fault(StatusCode);
+
+impl Middleware for Fault {
+    fn handle<'a>(
+        &'a self,
+        _: Request,
+        _: Next<'a>,
+    ) -> BoxFuture<'a, Result<TransportResponse, Error>> {
+        Box::pin(async move { Ok(TransportResponse::new(self.0, r#"{ "route": null }"#)) })
+    }
+}
+
+#[tokio::test]
+async fn middleware_wraps_requests_in_order() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let transport = Arc::new(
+        MemoryTransport::new().with_json("/v3/routes/2", r#"{ "route": { "route_id": 2 } }"#),
+    );
+    let client = common::builder(transport.clone())
+        .middleware(Audit {
+            name: "outer",
+            log: log.clone(),
+        })
+        .middleware(Reroute)
+        .middleware(Audit {
+            name: "inner",
+            log: log.clone(),
+        })
+        .build()
+        .unwrap();
+
+    let route = client
+        .get_route_by_route_id(RouteId(1), GetRouteByRouteIdParams::default())
+        .await
+        .unwrap();
+    assert_eq!(route.route.unwrap().route_id, Some(2));
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "outer > /v3/routes/1?",
+            "inner > /v3/routes/2?route_name=Glen%20Waverley%20line",
+            "inner < 200",
+            "outer < 200",
+        ]
+    );
+    let url = &transport.requests()[0];
+    assert!(url.contains("/v3/routes/2?route_name=Glen%20Waverley%20line&devid=1234"));
+    assert!(signing::verify("key", url));
+}
+
+#[tokio::test]
+async fn middleware_can_short_circuit() {
+    let transport = Arc::new(MemoryTransport::new());
+    let client = common::builder(transport.clone())
+        .middleware(Fault(StatusCode::OK))
+        .build()
+        .unwrap();
+    let route = client
+        .get_route_by_route_id(RouteId(1), GetRouteByRouteIdParams::default())
+        .await
+        .unwrap();
+    assert!(route.route.is_none());
+
+    let client = common::builder(transport.clone())
+        .middleware(Fault(StatusCode::SERVICE_UNAVAILABLE))
+        .build()
+        .unwrap();
+    let err = client
+        .get_route_by_route_id(RouteId(1), GetRouteByRouteIdParams::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    assert!(transport.requests().is_empty());
+}
+
+#[test]
+fn edits_query_parameters() {
+    let mut request = Request::new("/v3/stops/route/1/route_type/0?direction_id=2&expand=All");
+    assert_eq!(request.query("expand"), Some("All"));
+    assert_eq!(request.query("route_id"), None);
+
+    request.set_query("direction_id", "3");
+    request.remove_query("expand");
+    assert_eq!(
+        request.path,
+        "/v3/stops/route/1/route_type/0?direction_id=3"
+    );
+    request.remove_query("direction_id");
+    assert_eq!(request.path, "/v3/stops/route/1/route_type/0");
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use common::SlowTransport;
+use http::StatusCode;
+use ptv::*;
+
+const DIRECTIONS: &str = r#"{ "directions": [] }"#;
+
+#[tokio::test]
+async fn times_out_after_the_deadline() {
+    let transport = SlowTransport::new(Duration::from_secs(10));
+    let client = Client::with_transport("1234".into(), "key".into(), transport);
+
+    let options = CallOptions::new().timeout(Duration::from_millis(50));
+    let err = client
+        .get_directions_by_route_id_with_options(RouteId(1), options)
+        .await
+        .unwrap_err();
+    let Error::Timeout { elapsed } = err else {
+        panic!("expected a timeout, got {err:?}");
+    };
+    assert!(elapsed >= Duration::from_millis(50));
+    assert!(elapsed < Duration::from_secs(10));
+}
+
+#[tokio::test]
+async fn huge_timeouts_never_pass() {
+    let transport = SlowTransport::new(Duration::from_millis(10));
+    let client = Client::with_transport("1234".into(), "key".into(), transport);
+
+    let options = CallOptions::new().timeout(Duration::MAX);
+    client
+        .get_directions_by_route_id_with_options(RouteId(1), options)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn cancels_a_call_in_flight() {
+    let transport = Arc::new(SlowTransport::new(Duration::from_secs(10)));
+    let client = common::builder(transport.clone()).build().unwrap();
+
+    let cancel = CancellationToken::new();
+    let options = CallOptions::new().cancel_on(cancel.clone());
+    let call = client.get_directions_by_route_id_with_options(RouteId(1), options.clone());
+    let canceller = async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        cancel.cancel();
+    };
+    let (res, _) = tokio::join!(call, canceller);
+    assert!(matches!(res, Err(Error::Cancelled)));
+    assert_eq!(transport.paths().len(), 1);
+
+    // Once cancelled, nothing more is sent.
+    let res = client
+        .get_directions_by_route_id_with_options(RouteId(1), options)
+        .await;
+    assert!(matches!(res, Err(Error::Cancelled)));
+    assert_eq!(transport.paths().len(), 1);
+}
+
+#[tokio::test]
+async fn bypasses_the_cache() {
+    let transport =
+        Arc::new(MemoryTransport::new().with_json("/v3/directions/route/1", DIRECTIONS));
+    let client = common::builder(transport.clone())
+        .cache(Cache::memory(16))
+        .build()
+        .unwrap();
+
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    client
+        .get_directions_by_route_id_with_options(RouteId(1), CallOptions::new().bypass_cache(true))
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 2);
+
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn overrides_the_retry_policy() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .with_response(
+                "/v3/directions/route/1",
+                TransportResponse::new(StatusCode::SERVICE_UNAVAILABLE, "{}"),
+            )
+            .with_json("/v3/directions/route/1", DIRECTIONS),
+    );
+    let client = common::builder(transport.clone())
+        .retry(RetryPolicy::new(3).backoff(Duration::from_millis(1), Duration::from_millis(1)))
+        .build()
+        .unwrap();
+
+    let err = client
+        .get_directions_by_route_id_with_options(RouteId(1), CallOptions::new().no_retry())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::Status {
+                status: StatusCode::SERVICE_UNAVAILABLE,
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+    assert_eq!(transport.requests().len(), 1);
+
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert_eq!(transport.requests().len(), 2);
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use std::sync::Arc;
+
+use http::{HeaderValue, StatusCode};
+use ptv::{core::generated_types::*, *};
+
+const BODY: &str = r#"{ "route": { "route_id": 1, "route_name": "Alamein" } }"#;
+
+#[tokio::test]
+async fn returns_response_metadata() {
+    let mut response = TransportResponse::new(StatusCode::OK, BODY);
+    response
+        .headers
+        .insert("x-request-id", HeaderValue::from_static("abc123"));
+    let transport = Arc::new(MemoryTransport::new().with_response("/v3/routes", response));
+    let client = Client::with_transport("1234".into(), "key".into(), transport);
+
+    let res = client
+        .get_routes_with_response(GetRoutesParams {
+            route_name: Some("Alamein".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers["x-request-id"], "abc123");
+    assert_eq!(res.raw_body, BODY);
+    assert_eq!(res.request_path, "/v3/routes?route_name=Alamein");
+    assert_eq!(
+        res.value.route.unwrap().route_name.as_deref(),
+        Some("Alamein")
+    );
+}
+
+#[tokio::test]
+async fn cached_responses_keep_the_raw_body() {
+    let transport = Arc::new(MemoryTransport::new().with_json("/v3/routes", BODY));
+    let client = common::builder(transport.clone())
+        .cache(Cache::memory(8))
+        .build()
+        .unwrap();
+
+    let first = client
+        .get_routes_with_response(GetRoutesParams::default())
+        .await
+        .unwrap();
+    let second = client
+        .get_routes_with_response(GetRoutesParams::default())
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 1);
+    assert_eq!(first.raw_body, second.raw_body);
+    assert_eq!(second.status, StatusCode::OK);
+    assert_eq!(
+        format!("{:?}", first.value),
+        format!("{:?}", second.into_value())
+    );
+}
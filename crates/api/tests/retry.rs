@@ -0,0 +1,120 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use http::{HeaderValue, StatusCode, header::RETRY_AFTER};
+use ptv::*;
+
+const PATH: &str = "/v3/directions/route/1";
+const BODY: &str = r#"{ "directions": [] }"#;
+
+fn unavailable() -> TransportResponse {
+    TransportResponse::new(StatusCode::SERVICE_UNAVAILABLE, "")
+}
+
+fn client(transport: Arc<MemoryTransport>, policy: RetryPolicy) -> Client {
+    common::builder(transport)
+        .retry(policy.backoff(Duration::from_millis(1), Duration::from_millis(5)))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn retries_transient_statuses() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .with_response(PATH, unavailable())
+            .with_response(PATH, unavailable())
+            .with_json(PATH, BODY),
+    );
+    let client = client(transport.clone(), RetryPolicy::new(3));
+
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let transport = Arc::new(MemoryTransport::new().with_response(PATH, unavailable()));
+    let client = client(transport.clone(), RetryPolicy::new(4));
+
+    match client.get_directions_by_route_id(RouteId(1)).await {
+        Err(e @ Error::RetriesExhausted { attempts: 4, .. }) => {
+            assert_eq!(e.status(), Some(StatusCode::SERVICE_UNAVAILABLE))
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+    assert_eq!(transport.requests().len(), 4);
+}
+
+#[tokio::test]
+async fn does_not_retry_permanent_failures() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .with_response(PATH, TransportResponse::new(StatusCode::NOT_FOUND, ""))
+            .with_json(PATH, BODY),
+    );
+    let client = client(transport.clone(), RetryPolicy::new(3));
+
+    assert!(matches!(
+        client.get_directions_by_route_id(RouteId(1)).await,
+        Err(Error::Status {
+            status: StatusCode::NOT_FOUND,
+            ..
+        })
+    ));
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[test]
+fn honours_retry_after() {
+    let mut response = unavailable();
+    response
+        .headers
+        .insert(RETRY_AFTER, HeaderValue::from_static("2"));
+    assert_eq!(retry_after(&response.headers), Some(Duration::from_secs(2)));
+
+    let policy = RetryPolicy::default().backoff(Duration::from_millis(1), Duration::from_secs(1));
+    assert_eq!(
+        policy.delay(1, Some(&response.headers)),
+        Duration::from_secs(1)
+    );
+
+    response.headers.insert(
+        RETRY_AFTER,
+        HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+    );
+    assert_eq!(retry_after(&response.headers), Some(Duration::ZERO));
+}
+
+#[test]
+fn backoff_grows_exponentially() {
+    let policy = RetryPolicy::default()
+        .jitter(false)
+        .backoff(Duration::from_millis(100), Duration::from_millis(350));
+    assert_eq!(policy.delay(1, None), Duration::from_millis(100));
+    assert_eq!(policy.delay(2, None), Duration::from_millis(200));
+    assert_eq!(policy.delay(3, None), Duration::from_millis(350));
+}
+
+#[test]
+fn backoff_saturates_at_the_maximum() {
+    let policy = RetryPolicy::new(100).jitter(false);
+    assert_eq!(policy.delay(99, None), policy.max_backoff);
+
+    let policy = RetryPolicy::new(100).multiplier(10.0).jitter(false);
+    assert_eq!(policy.delay(30, None), policy.max_backoff);
+}
+
+#[test]
+fn rejects_invalid_multipliers() {
+    for multiplier in [-2.0, f64::NAN, f64::INFINITY] {
+        let res = Client::builder("1234", "key")
+            .retry(RetryPolicy::new(3).multiplier(multiplier))
+            .build();
+        assert!(
+            matches!(res, Err(Error::Config(_))),
+            "{multiplier} was accepted"
+        );
+    }
+}
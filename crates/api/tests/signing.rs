@@ -0,0 +1,79 @@
+use ptv::{signing::*, *};
+
+#[tokio::test]
+async fn matches_client_requests() {
+    let transport = std::sync::Arc::new(
+        MemoryTransport::new().with_json("/v3/directions/route/1", r#"{ "directions": [] }"#),
+    );
+    let client = Client::with_transport("1234".into(), "key".into(), transport.clone());
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+
+    let sent = &transport.requests()[0];
+    let signed = sign_path("1234", "key", "/v3/directions/route/1");
+    assert_eq!(*sent, signed.to_url(API_URL));
+    assert!(verify("key", sent));
+    assert!(!verify("other-key", sent));
+    assert_eq!(devid(sent), Some("1234"));
+}
+
+#[test]
+fn appends_to_existing_queries() {
+    assert_eq!(
+        sign_path("1", "key", "/v3/routes").path(),
+        "/v3/routes?devid=1"
+    );
+    assert_eq!(
+        sign_path("1", "key", "/v3/routes?").path(),
+        "/v3/routes?devid=1"
+    );
+    assert_eq!(
+        sign_path("1", "key", "/v3/routes?route_types=0&").path(),
+        "/v3/routes?route_types=0&devid=1"
+    );
+    assert_eq!(
+        sign_path("1", "key", "v3/routes").path(),
+        "/v3/routes?devid=1"
+    );
+}
+
+#[test]
+fn keeps_parameters_and_encoding_verbatim() {
+    let path = "/v3/search/Flinders%20Street?route_types=0&route_types=1&route_types=0";
+    let signed = sign_path("1", "key", path);
+    assert_eq!(signed.path(), format!("{path}&devid=1"));
+    assert!(verify("key", &signed.to_string()));
+
+    // Re-encoding the path invalidates the signature.
+    let reencoded = signed.to_string().replace("%20", "+");
+    assert!(!verify("key", &reencoded));
+}
+
+#[test]
+fn resigning_replaces_credentials() {
+    let first = sign_path("1", "key", "/v3/routes?route_types=0");
+    let second = sign_path("2", "other-key", &first.to_string());
+    assert_eq!(second.path(), "/v3/routes?route_types=0&devid=2");
+    assert!(verify("other-key", &second.to_string()));
+    assert_eq!(
+        unsigned_path(&second.to_url(API_URL)),
+        "/v3/routes?route_types=0"
+    );
+}
+
+#[test]
+fn rejects_malformed_urls() {
+    let signed = sign_path("1", "key", "/v3/routes").to_string();
+    assert!(!verify("key", "/v3/routes?devid=1"));
+    assert!(!verify("key", &format!("{signed}&extra=1")));
+    assert!(!verify("key", &signed.replace("devid=1", "devid=2")));
+    // Hex case is not significant.
+    let (path, signature) = signed.rsplit_once("&signature=").unwrap();
+    assert!(verify(
+        "key",
+        &format!("{path}&signature={}", signature.to_lowercase())
+    ));
+    assert_eq!(
+        redact(&signed),
+        "/v3/routes?devid=REDACTED&signature=REDACTED"
+    );
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "tower")]
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    task::{Context, Poll},
+};
+
+use ptv::{middleware::Request, *};
+use tower::{Layer, Service};
+
+/// Counts the requests passing through the service it wraps.
+#[derive(Clone)]
+struct Counting<S> {
+    inner: S,
+    count: Arc<AtomicUsize>,
+}
+
+impl<S: Service<Request>> Service<Request> for Counting<S> {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> S::Future {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        self.inner.call(request)
+    }
+}
+
+#[tokio::test]
+async fn client_is_a_tower_service() {
+    let transport = Arc::new(MemoryTransport::new().with_json("/v3/routes/1", "{}"));
+    let client = Client::with_transport("1234".into(), "key".into(), transport.clone());
+    let count = Arc::new(AtomicUsize::new(0));
+    let layer = tower::layer::layer_fn(|inner| Counting {
+        inner,
+        count: count.clone(),
+    });
+
+    let mut service = layer.layer(client);
+    let res = service.call(Request::new("/v3/routes/1")).await.unwrap();
+    assert_eq!(res.body, "{}");
+    let err = service
+        .call(Request::new("/v3/routes/2"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Transport(_)));
+
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert_eq!(transport.requests().len(), 2);
+}
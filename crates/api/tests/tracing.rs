@@ -0,0 +1,49 @@
+#![cfg(feature = "tracing")]
+use std::sync::{Arc, Mutex};
+
+use ptv::*;
+
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn endpoint_calls_are_traced_without_credentials() {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let client = Client::with_transport(
+        "1234".into(),
+        "secret-key".into(),
+        MemoryTransport::new().with_json("/v3/directions/route/1", r#"{ "directions": [] }"#),
+    );
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("get_directions_by_route_id"), "{output}");
+    assert!(
+        output.contains("path_template=\"/v3/directions/route/{route_id}\""),
+        "{output}"
+    );
+    assert!(output.contains("status=200"), "{output}");
+    assert!(
+        output.contains("devid=REDACTED&signature=REDACTED"),
+        "{output}"
+    );
+    assert!(!output.contains("1234"), "{output}");
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use http::StatusCode;
+use ptv::*;
+
+fn client(transport: MemoryTransport) -> (Client, std::sync::Arc<MemoryTransport>) {
+    let transport = std::sync::Arc::new(transport);
+    let client = Client::with_transport("1234".into(), "key".into(), transport.clone());
+    (client, transport)
+}
+
+#[tokio::test]
+async fn generated_method_uses_transport() {
+    let (client, transport) = client(MemoryTransport::new().with_json(
+        "/v3/directions/route/1",
+        r#"{
+            "directions": [
+                { "direction_id": 1, "route_id": 1, "direction_name": "City (Flinders Street)", "route_type": 0, "route_direction_description": "" }
+            ],
+            "status": { "version": "3.0", "health": 1 }
+        }"#,
+    ));
+
+    let res = client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    let directions = res.directions.unwrap();
+    assert_eq!(directions.len(), 1);
+    assert_eq!(
+        directions[0].direction_name.as_deref(),
+        Some("City (Flinders Street)")
+    );
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].starts_with(&format!(
+        "{API_URL}/v3/directions/route/1?devid=1234&signature="
+    )));
+}
+
+#[tokio::test]
+async fn error_status_is_reported() {
+    let (client, _) = client(MemoryTransport::new().with_response(
+        "/v3/directions/route/1",
+        TransportResponse::new(
+            StatusCode::FORBIDDEN,
+            r#"{ "message": "Forbidden", "status": { "version": "3.0", "health": 1 } }"#,
+        ),
+    ));
+
+    let err = client
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    match err {
+        Error::Status {
+            status,
+            error: Some(ApiError { message, .. }),
+            ..
+        } => {
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            assert_eq!(message, "Forbidden");
+        }
+        e => panic!("unexpected error: {e:?}"),
+    }
+}
+
+#[tokio::test]
+async fn deserialize_error_carries_path_and_body() {
+    let body = r#"{ "directions": [ { "direction_id": "one" } ] }"#;
+    let (client, _) = client(MemoryTransport::new().with_json("/v3/directions/route/1", body));
+
+    match client.get_directions_by_route_id(RouteId(1)).await {
+        Err(Error::Deserialize {
+            path, body: raw, ..
+        }) => {
+            assert_eq!(path, "directions[0].direction_id");
+            assert_eq!(raw, body);
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+}
+
+#[tokio::test]
+async fn unregistered_path_fails() {
+    let (client, _) = client(MemoryTransport::new());
+    assert!(matches!(
+        client.get_directions_by_route_id(RouteId(1)).await,
+        Err(Error::Transport(_))
+    ));
+}
+
+#[tokio::test]
+async fn builder_sets_base_url() {
+    let transport = std::sync::Arc::new(
+        MemoryTransport::new().with_json("/v3/directions/route/1", r#"{ "directions": [] }"#),
+    );
+    let client = common::builder(transport.clone())
+        .base_url("http://localhost:8080/")
+        .build()
+        .unwrap();
+
+    client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert!(transport.requests()[0].starts_with("http://localhost:8080/v3/directions/route/1?"));
+}
+
+#[test]
+fn builder_configures_reqwest() {
+    Client::builder("1234", "key")
+        .timeout(std::time::Duration::from_secs(5))
+        .connect_timeout(std::time::Duration::from_secs(1))
+        .user_agent("ptv-tests")
+        .default_header(
+            http::header::ACCEPT,
+            http::HeaderValue::from_static("application/json"),
+        )
+        .proxy(reqwest::Proxy::all("http://localhost:3128").unwrap())
+        .build()
+        .unwrap();
+}
@@ -0,0 +1,22 @@
+#![cfg(target_arch = "wasm32")]
+use ptv::*;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+async fn local_signer_signs_in_the_browser() {
+    let signer = Signer::Local(Credentials::new("1000000", "key"));
+    let signed = signer
+        .sign(&FetchTransport::new(), "/v3/routes?route_types=0")
+        .await
+        .unwrap();
+    assert!(signed.starts_with("/v3/routes?route_types=0&devid=1000000&signature="));
+    assert!(signing::verify("key", &signed));
+}
+
+#[wasm_bindgen_test]
+fn client_debug_redacts_the_key() {
+    let client = Client::new("1000000".into(), "secret".into()).base_url("http://localhost:8080");
+    let debug = format!("{client:?}");
+    assert!(!debug.contains("secret"));
+    assert!(debug.contains("http://localhost:8080"));
+}
@@ -6,7 +6,7 @@ use itertools::Itertools;
 use syn::{DeriveInput, parse::Parse, spanned::Spanned};
 use std::io::Write;
 
-use crate::types::{Context, PathName, SwaggerFile, ToRustTypeName, TypePath};
+use crate::types::{Context, Method, SwaggerFile, ToRustTypeName, TypePath};
 
 struct SwaggerClientArgs {
     path: String,
@@ -14,6 +14,16 @@ struct SwaggerClientArgs {
     skipped: Vec<String>,
     extra_names: HashMap<String, String>,
     path_skip: Vec<String>,
+    error: String,
+    blocking: Option<String>,
+    blocking_cfg: Option<String>,
+    response: Option<String>,
+    options: Option<String>,
+    options_cfg: Option<String>,
+    instrument: Option<String>,
+    instrument_cfg: Option<String>,
+    named: bool,
+    extras: bool,
 }
 
 impl Parse for SwaggerClientArgs {
@@ -23,6 +33,16 @@ impl Parse for SwaggerClientArgs {
         let mut extra_names = None;
         let mut skipped = Vec::new();
         let mut path_skip = Vec::new();
+        let mut error = None;
+        let mut blocking = None;
+        let mut blocking_cfg = None;
+        let mut response = None;
+        let mut options = None;
+        let mut options_cfg = None;
+        let mut instrument = None;
+        let mut instrument_cfg = None;
+        let mut named = false;
+        let mut extras = false;
 
         while !input.is_empty() {
             let ident: syn::Ident = input.parse()?;
@@ -37,6 +57,46 @@ impl Parse for SwaggerClientArgs {
                     let lit: syn::LitStr = input.parse()?;
                     strip_prefix = Some(lit.value());
                 }
+                "error" => {
+                    let lit: syn::LitStr = input.parse()?;
+                    error = Some(lit.value());
+                }
+                "blocking" => {
+                    let lit: syn::LitStr = input.parse()?;
+                    blocking = Some(lit.value());
+                }
+                "blocking_cfg" => {
+                    let lit: syn::LitStr = input.parse()?;
+                    blocking_cfg = Some(lit.value());
+                }
+                "response" => {
+                    let lit: syn::LitStr = input.parse()?;
+                    response = Some(lit.value());
+                }
+                "options" => {
+                    let lit: syn::LitStr = input.parse()?;
+                    options = Some(lit.value());
+                }
+                "options_cfg" => {
+                    let lit: syn::LitStr = input.parse()?;
+                    options_cfg = Some(lit.value());
+                }
+                "instrument" => {
+                    let lit: syn::LitStr = input.parse()?;
+                    instrument = Some(lit.value());
+                }
+                "instrument_cfg" => {
+                    let lit: syn::LitStr = input.parse()?;
+                    instrument_cfg = Some(lit.value());
+                }
+                "named" => {
+                    let lit: syn::LitBool = input.parse()?;
+                    named = lit.value;
+                }
+                "extras" => {
+                    let lit: syn::LitBool = input.parse()?;
+                    extras = lit.value;
+                }
                 "path_skip" => {
                     let skips: syn::ExprArray = input.parse()?;
                     for expr in skips.elems.iter() {
@@ -134,12 +194,23 @@ impl Parse for SwaggerClientArgs {
             strip_prefix,
             skipped,
             extra_names: extra_names.unwrap_or_default(),
+            error: error.unwrap_or_else(|| "Error".to_string()),
+            blocking,
+            blocking_cfg,
+            response,
+            options,
+            options_cfg,
+            instrument,
+            instrument_cfg,
+            named,
+            extras,
         })
     }
 }
 
 #[macro_use]
 mod types;
+mod openapi;
 
 fn derive_actual(
     input: DeriveInput,
@@ -157,8 +228,10 @@ fn derive_actual(
         writeln!(debug_file, "# Swagger Client Generation Debug Output").ok();
     }
 
-    let mut deserializer =
-        serde_json::Deserializer::from_reader(std::fs::File::open(&args.path).unwrap());
+    let document: serde_json::Value = serde_json::from_reader(
+        std::fs::File::open(&args.path).with_context(|| format!("Couldn't open {}", args.path))?,
+    )?;
+    let document = openapi::normalize(document)?;
     let mut constant_parameters = Vec::new();
     constant_parameters.extend(args.skipped);
 
@@ -173,7 +246,7 @@ fn derive_actual(
         });
     }
     println!("Constant parameters: {:?}", &constant_parameters);
-    let result: SwaggerFile = match serde_path_to_error::deserialize(&mut deserializer) {
+    let result: SwaggerFile = match serde_path_to_error::deserialize(document) {
         Ok(v) => v,
         Err(e) => {
             eprintln!("Error at path: {}", e.path());
@@ -204,6 +277,7 @@ fn derive_actual(
         types: names,
         name_stack: Default::default(),
         extra_types: args.extra_names,
+        extras: args.extras,
     });
     let mut module = codegen::Module::new("generated_types");
     {
@@ -249,17 +323,18 @@ writeln!(debug_file,
         for (i,(path_name, path_item)) in paths.iter().enumerate() {
             //        println!("path_name: {:?}", path_name);
             for (method, operation) in &path_item.methods {
-                let ret_type = operation
-                    .responses
-                    .get("200")
-                    .unwrap()
-                    .schema
-                    .as_ref()
-                    .unwrap()
-                    .schema_object
-                    .to_rust_type_name(context.clone())?;
-
-                let name = if !operation.parameters.path.is_empty() {
+                // The first success response decides the return type; one without a schema
+                // (e.g. `204 No Content`) returns `()`.
+                let ret_type = ["200", "201", "202", "204"]
+                    .iter()
+                    .find_map(|code| operation.responses.get(*code))
+                    .and_then(|response| response.schema.as_ref())
+                    .map(|schema| schema.schema_object.to_rust_type_name(context.clone()))
+                    .transpose()?;
+
+                let name = if let (false, Some(ret_type)) =
+                    (operation.parameters.path.is_empty(), &ret_type)
+                {
                     let rust_type = ret_type.to_snake_case().replace("_response", "");
                     format!(
                         "{}_{}_by_{}",
@@ -349,53 +424,116 @@ writeln!(debug_file,
                     }
                 };
 
-                let ret_type = format!(
-                    "{}::{}",
-                    {
+                // Header parameters get their own struct, serialized under their original names.
+                let obj_headers_name = format!("{}Headers", name.to_upper_camel_case());
+                let _handle = context.handle_with_name(obj_headers_name.clone());
+                let obj_headers = operation
+                    .parameters
+                    .header
+                    .iter()
+                    .filter(|param| !context.constant_parameters.contains(&param.name))
+                    .map(|param| {
+                        let param_name = param.name.to_snake_case();
+                        let _handle = context.handle_with_name(param_name.clone());
+                        let rust_type = if let Some(ty) =
+                            context.extra_types.get(&param_name.to_upper_camel_case())
+                        {
+                            ty.clone()
+                        } else {
+                            param
+                                .r#type
+                                .schema_object
+                                .to_rust_type_name(context.clone())
+                                .unwrap()
+                        };
+                        let mut field =
+                            codegen::Field::new(&param_name, format!("Option<{}>", rust_type));
+                        field.vis("pub");
+                        if let Some(ref docs) = param.description {
+                            field.doc(docs);
+                        }
+                        field.annotation(format!(
+                            "#[serde(rename = {:?}, skip_serializing_if = \"Option::is_none\")]",
+                            param.name
+                        ));
+                        field
+                    })
+                    .collect_vec();
+                let func_headers_name = {
+                    if obj_headers.is_empty() {
+                        None
+                    } else {
                         context!(context, scope);
-                        scope.name.clone()
-                    },
-                    ret_type.to_upper_camel_case()
-                );
 
-                let mut scope = context.scope.borrow_mut();
-                let scope = scope.new_impl(&input.ident.to_string());
-                let mut func = scope
-                    .new_fn(&name.to_snake_case())
-                    .vis("pub")
-                    .ret(format!("Result<{},Error>", ret_type));
+                        let func_headers = scope
+                            .new_struct(&obj_headers_name)
+                            .vis("pub")
+                            .derive("Default");
+                        struc_opts!(func_headers);
+                        for field in obj_headers {
+                            func_headers.push_field(field);
+                        }
+                        Some(obj_headers_name)
+                    }
+                };
+
+                let scope_name = {
+                    context!(context, scope);
+                    scope.name.clone()
+                };
+                let ret_type = match ret_type {
+                    Some(ret_type) => format!("{}::{}", scope_name, ret_type.to_upper_camel_case()),
+                    None => "()".to_string(),
+                };
+                let body_type = match &operation.parameters.body {
+                    Some(body) => {
+                        let ty = format!(
+                            "{}::{}",
+                            scope_name,
+                            body.schema
+                                .schema_object
+                                .to_rust_type_name(context.clone())?
+                                .to_upper_camel_case()
+                        );
+                        Some(if body.required { ty } else { format!("Option<{}>", ty) })
+                    }
+                    None => None,
+                };
+
                 let mut docs = format!(
                     "Auto-generated method for the `{}` `{}` endpoint.",
-                    "GET", path_name.internal
+                    method.to_string().to_uppercase(),
+                    path_name.internal
                 );
                 if let Some(ref summary) = operation.summary {
                     docs.push_str("\n\n");
-                    docs.push_str(&summary);
+                    docs.push_str(summary);
                 }
-                func.doc(&docs);
-                func.set_async(true);
-                func.arg_ref_self();
+                let mut span_fields = vec![format!("path_template = {:?}", path_name.internal)];
                 for (param_name, rust_type, _) in path_params.iter() {
-                    func = func.arg(
-                        &param_name,
-                        if rust_type == "String" {
-                            "impl AsRef<str>"
-                        } else {
-                            &rust_type
-                        },
-                    );
+                    span_fields.push(if rust_type == "String" {
+                        format!("{0} = %{0}.as_ref()", param_name)
+                    } else {
+                        format!("{0} = ?{0}", param_name)
+                    });
                 }
-                if let Some(func_param_name) = &func_param_name {
-                    func.arg(
-                        "params",
-                        format!("{}::{}", "generated_types", func_param_name),
-                    );
+                if func_param_name.is_some() {
+                    span_fields.push("params = ?params".to_string());
                 }
+                let call_args = path_params
+                    .iter()
+                    .map(|(param_name, _, _)| param_name.as_str())
+                    .chain(func_param_name.as_ref().map(|_| "params"))
+                    .chain(func_headers_name.as_ref().map(|_| "headers"))
+                    .chain(body_type.as_ref().map(|_| "body"))
+                    .join(", ");
+
+                let mut lines = Vec::new();
                 let mut path_name = path_name.clone();
-                for (param_name, ty, original_name) in path_params {
+                for (param_name, ty, original_name) in path_params.iter() {
                     let to_replace = format!("{{{}}}", original_name);
                     let replacement = if ty == "String" {
-                        func.line(format!(
+                        lines.push(format!(
                         "let {0} =  url_escape::encode_path(&clean({0}.as_ref().to_string())).into_owned();",
                         &param_name
                     ));
@@ -407,11 +545,165 @@ writeln!(debug_file,
                 }
                 //            println!("Generating function a: {}", path_name.internal);
 
-                func.line(format!("let path = format!(\"{}\");", &path_name.internal));
-                if let Some(_params) = func_param_name {
-                    func.line("self.rq(format!(\"{}?{}\", path, to_query(params))).await");
+                lines.push(format!("let path = format!(\"{}\");", &path_name.internal));
+                let mut path_expr = if func_param_name.is_some() {
+                    "format!(\"{}?{}\", path, to_query(params)?)"
                 } else {
-                    func.line("self.rq(path).await");
+                    "path"
+                };
+                // Anything but a plain `GET` is sent as a `Request`, with the headers set and the
+                // body as JSON.
+                if *method != Method::Get || func_headers_name.is_some() || body_type.is_some() {
+                    lines.push(format!("let request = Request::{}({});", method, path_expr));
+                    if func_headers_name.is_some() {
+                        lines.push("let request = request.headers_from(&headers)?;".to_string());
+                    }
+                    match &operation.parameters.body {
+                        Some(body) if body.required => {
+                            lines.push("let request = request.json(&body)?;".to_string())
+                        }
+                        Some(_) => lines.push(
+                            "let request = match body { Some(body) => request.json(&body)?, None => request };"
+                                .to_string(),
+                        ),
+                        None => {}
+                    }
+                    path_expr = "request";
+                }
+
+                // Every operation gets a plain method, plus one returning the whole response
+                // when the derive is given a `response` type, and one taking per-call options
+                // when given an `options` type, compiled only under `options_cfg` if given.
+                let mut variants = vec![(
+                    name.to_snake_case(),
+                    ret_type.clone(),
+                    "rq",
+                    docs.clone(),
+                    None,
+                )];
+                if let Some(ref response) = args.response {
+                    variants.push((
+                        format!("{}_with_response", name.to_snake_case()),
+                        format!("{}<{}>", response, ret_type),
+                        "rq_response",
+                        format!(
+                            "Like [`{}`](Self::{0}), but also returns the response's status, headers, timing and raw body.",
+                            name.to_snake_case()
+                        ),
+                        None,
+                    ));
+                }
+                if let Some(ref options) = args.options {
+                    variants.push((
+                        format!("{}_with_options", name.to_snake_case()),
+                        ret_type.clone(),
+                        "rq_with_options",
+                        format!(
+                            "Like [`{}`](Self::{0}), with options for this call only.",
+                            name.to_snake_case()
+                        ),
+                        Some(options),
+                    ));
+                }
+
+                for (fn_name, fn_ret, rq, fn_docs, fn_options) in variants {
+                    let mut func = codegen::Function::new(&fn_name);
+                    func.vis("pub")
+                        .ret(format!("Result<{},{}>", fn_ret, args.error));
+                    func.doc(&fn_docs);
+                    func.arg_ref_self();
+                    for (param_name, rust_type, _) in path_params.iter() {
+                        func.arg(
+                            param_name,
+                            if rust_type == "String" {
+                                "impl AsRef<str>"
+                            } else {
+                                rust_type
+                            },
+                        );
+                    }
+                    if let Some(func_param_name) = &func_param_name {
+                        func.arg(
+                            "params",
+                            format!("{}::{}", "generated_types", func_param_name),
+                        );
+                    }
+                    if let Some(func_headers_name) = &func_headers_name {
+                        func.arg(
+                            "headers",
+                            format!("{}::{}", "generated_types", func_headers_name),
+                        );
+                    }
+                    if let Some(body_type) = &body_type {
+                        func.arg("body", body_type);
+                    }
+                    if let Some(options) = fn_options {
+                        func.arg("options", options);
+                        if let Some(ref cfg) = args.options_cfg {
+                            func.attr(&format!("cfg({})", cfg));
+                        }
+                    }
+                    let (call_args, path_expr) = match fn_options {
+                        Some(_) => (
+                            [call_args.as_str(), "options"]
+                                .iter()
+                                .filter(|arg| !arg.is_empty())
+                                .join(", "),
+                            format!("{}, options", path_expr),
+                        ),
+                        None => (call_args.clone(), path_expr.to_string()),
+                    };
+                    // The blocking mirror takes the same arguments and hands them to the async method,
+                    // compiled only under `blocking_cfg` if given.
+                    let blocking_func = args.blocking.as_ref().map(|target| {
+                        let mut blocking_func = func.clone();
+                        blocking_func.line(format!(
+                            "self.block_on(self.inner.{}({}))",
+                            fn_name, call_args
+                        ));
+                        (target, blocking_func)
+                    });
+                    // With `instrument`, each method gets a span from that attribute macro, e.g.
+                    // `tracing::instrument`, applied only under `instrument_cfg` if given.
+                    if let Some(ref instrument) = args.instrument {
+                        let instrument = format!(
+                            "{}(name = {:?}, skip_all, fields({}))",
+                            instrument,
+                            fn_name,
+                            span_fields.join(", ")
+                        );
+                        match args.instrument_cfg {
+                            Some(ref cfg) => {
+                                func.attr(&format!("cfg_attr({}, {})", cfg, instrument))
+                            }
+                            None => func.attr(&instrument),
+                        };
+                    }
+                    func.set_async(true);
+                    for line in &lines {
+                        func.line(line);
+                    }
+                    // With `named`, the request methods also get the operation's name, e.g. for metrics.
+                    if args.named {
+                        func.line(format!(
+                            "self.{}_named({:?}, {}).await",
+                            rq,
+                            name.to_snake_case(),
+                            path_expr
+                        ));
+                    } else {
+                        func.line(format!("self.{}({}).await", rq, path_expr));
+                    }
+
+                    let mut scope = context.scope.borrow_mut();
+                    scope.new_impl(&input.ident.to_string()).push_fn(func);
+                    if let Some((target, blocking_func)) = blocking_func {
+                        let blocking_impl = scope.new_impl(target);
+                        if let Some(ref cfg) = args.blocking_cfg {
+                            blocking_impl.r#macro(&format!("#[cfg({})]", cfg));
+                        }
+                        blocking_impl.push_fn(blocking_func);
+                    }
                 }
                 if let Some(ref mut debug_file) = debug_file {
                     // format like the readme table
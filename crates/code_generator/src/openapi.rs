@@ -0,0 +1,434 @@
+//! OpenAPI 3.x input.
+//!
+//! [`SwaggerFile`](crate::types::SwaggerFile) models Swagger 2.0, so an OpenAPI 3.x document
+//! is rewritten into that layout before it's deserialized:
+//!
+//! - `components/schemas` become `definitions`, and `$ref`s to them are rewritten to match;
+//!   `$ref`s to other components (parameters, request bodies, responses) are inlined.
+//! - The first of `servers` gives the `host` and `schemes`, and its path prefixes every path.
+//! - A JSON `requestBody` becomes an `in: body` parameter, and JSON response `content` the
+//!   response's `schema`.
+//! - A parameter's `schema` is inlined into the parameter, and path-level parameters are
+//!   copied to each operation.
+//!
+//! Schemas of either version are then simplified: `allOf` is merged into a single object,
+//! and `nullable` properties (or 3.1 `null` types) are no longer required. `oneOf` and
+//! `anyOf` are kept, and generated as untagged enums.
+
+use anyhow::{Context, bail};
+use serde_json::{Map, Value, json};
+
+/// Methods the generator knows, see [`Method`](crate::types::Method).
+const METHODS: [&str; 4] = ["get", "post", "put", "delete"];
+
+/// Rewrite `document` into the Swagger 2.0 layout, see the [module docs](self).
+pub fn normalize(document: Value) -> anyhow::Result<Value> {
+    let mut document = if document.get("openapi").is_some() {
+        from_openapi(document)?
+    } else {
+        document
+    };
+    let definitions = document
+        .get("definitions")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    if let Some(Value::Object(schemas)) = document.get_mut("definitions") {
+        for schema in schemas.values_mut() {
+            simplify(schema, &definitions)?;
+        }
+    }
+    if let Some(Value::Object(paths)) = document.get_mut("paths") {
+        for operation in paths
+            .values_mut()
+            .filter_map(Value::as_object_mut)
+            .flat_map(|item| item.values_mut())
+        {
+            if let Some(Value::Array(parameters)) = operation.get_mut("parameters") {
+                for parameter in parameters {
+                    if let Some(schema) = parameter.get_mut("schema") {
+                        simplify(schema, &definitions)?;
+                    }
+                    simplify(parameter, &definitions)?;
+                }
+            }
+            if let Some(Value::Object(responses)) = operation.get_mut("responses") {
+                for schema in responses
+                    .values_mut()
+                    .filter_map(|response| response.get_mut("schema"))
+                {
+                    simplify(schema, &definitions)?;
+                }
+            }
+        }
+    }
+    Ok(document)
+}
+
+fn from_openapi(document: Value) -> anyhow::Result<Value> {
+    let version = document["openapi"].as_str().unwrap_or_default();
+    if !version.starts_with("3.") {
+        bail!("Unsupported OpenAPI version {version:?}");
+    }
+    let components = document.get("components").cloned().unwrap_or_default();
+    let mut document = document;
+    inline_components(&mut document, &components)?;
+
+    let mut definitions = components
+        .get("schemas")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    inline_components(&mut definitions, &components)?;
+    rename_schema_refs(&mut document);
+    rename_schema_refs(&mut definitions);
+
+    let (schemes, host, base_path) = server(document.get("servers"));
+    let mut paths = Map::new();
+    if let Some(Value::Object(items)) = document.get("paths") {
+        for (path, item) in items {
+            paths.insert(format!("{base_path}{path}"), path_item(item));
+        }
+    }
+    Ok(json!({
+        "swagger": "3.0",
+        "info": document["info"],
+        "host": host,
+        "schemes": schemes,
+        "paths": paths,
+        "definitions": definitions,
+    }))
+}
+
+/// Replace `$ref`s to components other than schemas with the component itself.
+fn inline_components(value: &mut Value, components: &Value) -> anyhow::Result<()> {
+    match value {
+        Value::Object(map) => {
+            let target = map
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|r#ref| r#ref.strip_prefix("#/components/"))
+                .filter(|path| !path.starts_with("schemas/"))
+                .map(str::to_string);
+            if let Some(path) = target {
+                let mut component = components
+                    .pointer(&format!("/{path}"))
+                    .cloned()
+                    .with_context(|| format!("No component at #/components/{path}"))?;
+                inline_components(&mut component, components)?;
+                *value = component;
+                return Ok(());
+            }
+            for child in map.values_mut() {
+                inline_components(child, components)?;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                inline_components(item, components)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn rename_schema_refs(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                match child {
+                    Value::String(r#ref) if key == "$ref" => {
+                        *r#ref = r#ref.replace("#/components/schemas/", "#/definitions/");
+                    }
+                    _ => rename_schema_refs(child),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(rename_schema_refs),
+        _ => {}
+    }
+}
+
+/// The schemes, host and base path of the first server, with its variables at their
+/// defaults.
+fn server(servers: Option<&Value>) -> (Vec<String>, String, String) {
+    let Some(server) = servers.and_then(|servers| servers.get(0)) else {
+        return (Vec::new(), String::new(), String::new());
+    };
+    let mut url = server["url"].as_str().unwrap_or_default().to_string();
+    if let Some(Value::Object(variables)) = server.get("variables") {
+        for (name, variable) in variables {
+            let default = variable["default"].as_str().unwrap_or_default();
+            url = url.replace(&format!("{{{name}}}"), default);
+        }
+    }
+    let (schemes, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => (vec![scheme.to_lowercase()], rest),
+        None => (Vec::new(), url.as_str()),
+    };
+    let (host, base_path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+    (
+        schemes,
+        host.to_string(),
+        base_path.trim_end_matches('/').to_string(),
+    )
+}
+
+fn path_item(item: &Value) -> Value {
+    let shared = item["parameters"].as_array().cloned().unwrap_or_default();
+    let mut operations = Map::new();
+    for method in METHODS {
+        let Some(Value::Object(operation)) = item.get(method) else {
+            continue;
+        };
+        let mut operation = operation.clone();
+        let own = operation
+            .remove("parameters")
+            .and_then(|parameters| parameters.as_array().cloned())
+            .unwrap_or_default();
+        // An operation's parameter overrides a path-level one with the same name and location.
+        let mut parameters = shared
+            .iter()
+            .filter(|parameter| {
+                !own.iter().any(|other| {
+                    other["name"] == parameter["name"] && other["in"] == parameter["in"]
+                })
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        parameters.extend(own);
+        let mut parameters = parameters.into_iter().map(parameter).collect::<Vec<_>>();
+        if let Some(body) = operation.remove("requestBody")
+            && let Some(schema) = json_schema(&body)
+        {
+            parameters.push(json!({
+                "name": "body",
+                "in": "body",
+                "required": body["required"].as_bool().unwrap_or(false),
+                "description": body["description"],
+                "schema": schema,
+            }));
+        }
+        operation.insert("parameters".to_string(), parameters.into());
+        if let Some(Value::Object(responses)) = operation.get_mut("responses") {
+            for response in responses.values_mut().filter_map(Value::as_object_mut) {
+                if let Some(content) = response.remove("content")
+                    && let Some(schema) = json_schema(&json!({ "content": content }))
+                {
+                    response.insert("schema".to_string(), schema);
+                }
+            }
+        }
+        operations.insert(method.to_string(), operation.into());
+    }
+    operations.into()
+}
+
+/// A parameter with its `schema` inlined, as in Swagger 2.0.
+fn parameter(parameter: Value) -> Value {
+    let Value::Object(mut parameter) = parameter else {
+        return parameter;
+    };
+    if let Some(Value::Object(schema)) = parameter.remove("schema") {
+        for (key, value) in schema {
+            parameter.entry(key).or_insert(value);
+        }
+    }
+    parameter.entry("required").or_insert(false.into());
+    parameter.into()
+}
+
+/// The schema of the JSON media type in `value`'s `content`, if there is one.
+fn json_schema(value: &Value) -> Option<Value> {
+    let content = value.get("content")?.as_object()?;
+    content
+        .get("application/json")
+        .or_else(|| {
+            content
+                .iter()
+                .find(|(media_type, _)| media_type.contains("json"))
+                .map(|(_, media)| media)
+        })?
+        .get("schema")
+        .cloned()
+}
+
+fn simplify(schema: &mut Value, definitions: &Value) -> anyhow::Result<()> {
+    let Value::Object(schema) = schema else {
+        return Ok(());
+    };
+    if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+        for property in properties.values_mut() {
+            simplify(property, definitions)?;
+        }
+    }
+    for key in ["items", "additionalProperties"] {
+        if let Some(child) = schema.get_mut(key) {
+            simplify(child, definitions)?;
+        }
+    }
+    for key in ["allOf", "oneOf", "anyOf"] {
+        if let Some(Value::Array(members)) = schema.get_mut(key) {
+            for member in members {
+                simplify(member, definitions)?;
+            }
+        }
+    }
+
+    // 3.1 spells `nullable` as a `null` type.
+    if let Some(Value::Array(types)) = schema.get("type") {
+        let count = types.len();
+        let types = types
+            .iter()
+            .filter(|ty| ty.as_str() != Some("null"))
+            .cloned()
+            .collect::<Vec<_>>();
+        if let [ty] = types.as_slice() {
+            let ty = ty.clone();
+            schema.insert("type".to_string(), ty);
+            if count > 1 {
+                schema.insert("nullable".to_string(), true.into());
+            }
+        }
+    }
+
+    for key in ["oneOf", "anyOf"] {
+        let Some(Value::Array(members)) = schema.get_mut(key) else {
+            continue;
+        };
+        let count = members.len();
+        members.retain(|member| member.get("type").and_then(Value::as_str) != Some("null"));
+        let nullable = members.len() < count;
+        if let [member] = members.as_slice() {
+            // Only one alternative (and maybe `null`), so it's that alternative.
+            let member = member.clone();
+            schema.remove(key);
+            merge_missing(schema, member);
+        } else {
+            // The alternatives decide the type, see `TypeUntagged::OneOf`.
+            schema.remove("type");
+        }
+        if nullable {
+            schema.insert("nullable".to_string(), true.into());
+        }
+    }
+
+    if let Some(Value::Array(members)) = schema.remove("allOf") {
+        match members.as_slice() {
+            // The usual way to add a description or `nullable` to a `$ref`, so keep its name.
+            [member] if is_ref(member) && !schema.contains_key("properties") => {
+                schema.insert("$ref".to_string(), member["$ref"].clone());
+            }
+            _ => {
+                let mut merged = Map::new();
+                for member in members {
+                    merge_all_of(&mut merged, resolve(member, definitions)?);
+                }
+                let own = std::mem::take(schema);
+                merge_all_of(&mut merged, own.clone());
+                *schema = merged;
+                for key in ["description", "nullable"] {
+                    if let Some(value) = own.get(key) {
+                        schema.insert(key.to_string(), value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    // A `nullable` property may be `null` even when it's required.
+    let nullable = match schema.get("properties") {
+        Some(Value::Object(properties)) => properties
+            .iter()
+            .filter(|(_, property)| property["nullable"] == true)
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+    if let Some(Value::Array(required)) = schema.get_mut("required") {
+        required.retain(|name| !nullable.iter().any(|nullable| name == nullable));
+    }
+
+    // A `$ref` with a `type` next to it would be read as an inline type.
+    if schema.contains_key("$ref") {
+        schema.remove("type");
+    }
+    if schema.contains_key("properties") && !schema.contains_key("type") {
+        schema.insert("type".to_string(), "object".into());
+    }
+    // `additionalProperties: true` is the default for objects, and `false` isn't enforced.
+    if let Some(Value::Bool(_)) = schema.get("additionalProperties") {
+        schema.remove("additionalProperties");
+    }
+    Ok(())
+}
+
+fn is_ref(schema: &Value) -> bool {
+    schema
+        .as_object()
+        .is_some_and(|schema| schema.contains_key("$ref"))
+}
+
+/// `schema`, or the simplified definition it refers to.
+fn resolve(schema: Value, definitions: &Value) -> anyhow::Result<Map<String, Value>> {
+    let mut schema = match schema["$ref"].as_str() {
+        Some(target) => {
+            let name = target
+                .strip_prefix("#/definitions/")
+                .with_context(|| format!("Can't resolve {target:?} in allOf"))?;
+            definitions
+                .get(name)
+                .cloned()
+                .with_context(|| format!("No definition for {target:?}"))?
+        }
+        None => schema,
+    };
+    simplify(&mut schema, definitions)?;
+    match schema {
+        Value::Object(schema) => Ok(schema),
+        schema => bail!("Expected an object schema in allOf, got {schema}"),
+    }
+}
+
+/// Add the properties and requirements of `member` to `merged`.
+fn merge_all_of(merged: &mut Map<String, Value>, member: Map<String, Value>) {
+    merged.insert("type".to_string(), "object".into());
+    for (key, value) in member {
+        match (key.as_str(), value) {
+            ("properties", Value::Object(properties)) => {
+                let merged = merged
+                    .entry("properties")
+                    .or_insert_with(|| json!({}))
+                    .as_object_mut()
+                    .unwrap();
+                merged.extend(properties);
+            }
+            ("required", Value::Array(required)) => {
+                let merged = merged
+                    .entry("required")
+                    .or_insert_with(|| json!([]))
+                    .as_array_mut()
+                    .unwrap();
+                for name in required {
+                    if !merged.contains(&name) {
+                        merged.push(name);
+                    }
+                }
+            }
+            ("additionalProperties", value) => {
+                merged.insert(key, value);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn merge_missing(schema: &mut Map<String, Value>, member: Value) {
+    if let Value::Object(member) = member {
+        for (key, value) in member {
+            schema.entry(key).or_insert(value);
+        }
+    }
+}
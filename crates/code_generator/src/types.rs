@@ -38,6 +38,7 @@ macro_rules! struc_opts {
 pub struct TypePath(pub String);
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct Context {
     pub types: std::collections::HashMap<TypePath, String>,
     pub extra_types: std::collections::HashMap<String, String>,
@@ -46,6 +47,8 @@ pub struct Context {
     // probably not the best way, but it makes sense
     pub name_stack: RefCell<VecDeque<String>>,
     pub strip_prefix: Option<String>,
+    // Whether structs without additionalProperties keep unknown keys in `extras`
+    pub extras: bool,
 }
 
 impl Context {
@@ -227,7 +230,7 @@ impl ToRustTypeName for TypeTagged {
 
                     //println!("Generating enum: {}", &enum_name);
                     if let Some(enuma) = context.extra_types.get(&enum_name) {
-                        return Ok(enuma.clone());
+                        Ok(enuma.clone())
                     } else {
                         let enum_name = context.get_name();
                         context!(context, scope);
@@ -273,7 +276,7 @@ impl ToRustTypeName for TypeTagged {
                         .context("Expected extra name for enum")?
                         .to_upper_camel_case();
                     if let Some(enuma) = context.extra_types.get(&enum_name) {
-                        return Ok(enuma.clone());
+                        Ok(enuma.clone())
                     } else {
                         let enum_name = context.get_name();
                         context!(context, scope);
@@ -337,7 +340,7 @@ impl ToRustTypeName for TypeTagged {
                     .context("Expected extra name for object")?
                     .to_upper_camel_case();
                 if let Some(structa) = context.extra_types.get(&struct_name) {
-                    return Ok(structa.clone());
+                    Ok(structa.clone())
                 } else {
                     let struct_name = context.get_name();
                     //                    println!("Generating struct: {}", struct_name);
@@ -384,14 +387,19 @@ impl ToRustTypeName for TypeTagged {
                         field.vis("pub");
                         field.annotation(r#"#[serde(flatten)]"#);
                         strukt.push_field(field);
-                    } // else {
-                    //   let mut field = codegen::Field::new(
-                    //       "additional_properties",
-                    //       "std::collections::HashMap<String, serde_json::Value>".to_string(),
-                    //   );
-                    //   field.vis("pub");
-                    //   field.annotation(r#"#[serde(flatten)]"#);
-                    //   strukt.push_field(field);
+                    } else if context.extras {
+                        // Keys the spec doesn't know about are kept rather than silently dropped.
+                        let mut field = codegen::Field::new(
+                            "extras",
+                            "std::collections::HashMap<String, serde_json::Value>",
+                        );
+                        field.vis("pub");
+                        field.doc("Fields returned by the API that are not in the spec.");
+                        field.annotation(
+                            r#"#[serde(flatten, skip_serializing_if = "std::collections::HashMap::is_empty")]"#,
+                        );
+                        strukt.push_field(field);
+                    }
 
                     {
                         context!(context, scope);
@@ -412,6 +420,11 @@ pub enum TypeUntagged {
         #[serde(rename = "$ref")]
         r#ref: TypePath,
     },
+    /// `oneOf` or `anyOf`, generated as an untagged enum of the alternatives.
+    OneOf {
+        #[serde(rename = "oneOf", alias = "anyOf")]
+        one_of: Vec<Type>,
+    },
     //   Extra(serde_json::Value),
 }
 
@@ -431,6 +444,44 @@ impl ToRustTypeName for TypeUntagged {
                     Ok("serde_json::Value".to_string())
                 }
             }
+            TypeUntagged::OneOf { one_of } => {
+                let enum_name = context
+                    .get_top_name()
+                    .context("Expected extra name for oneOf")?
+                    .to_upper_camel_case();
+                if let Some(enuma) = context.extra_types.get(&enum_name) {
+                    return Ok(enuma.clone());
+                }
+                let enum_name = context.get_name();
+                let mut variants: Vec<(String, String)> = Vec::new();
+                for (i, alternative) in one_of.iter().enumerate() {
+                    let _handle = context.handle_with_name(format!("variant{}", i));
+                    let rust_type = alternative
+                        .schema_object
+                        .to_rust_type_name(context.clone())?;
+                    // Named after the type, e.g. `Stop(Stop)` or `String(String)`.
+                    let mut variant_name = rust_type
+                        .rsplit("::")
+                        .next()
+                        .unwrap_or(&rust_type)
+                        .to_upper_camel_case();
+                    if variants.iter().any(|(name, _)| name == &variant_name) {
+                        variant_name = format!("{}{}", variant_name, i);
+                    }
+                    variants.push((variant_name, rust_type));
+                }
+                context!(context, scope);
+                let enm = scope.new_enum(&enum_name);
+                enm.vis("pub");
+                enm.derive("Debug");
+                enm.derive("Serialize");
+                enm.derive("Deserialize");
+                enm.r#macro("#[serde(untagged)]");
+                for (variant_name, rust_type) in &variants {
+                    enm.new_variant(variant_name).tuple(rust_type);
+                }
+                Ok(enum_name)
+            }
         }
     }
 }
@@ -483,6 +534,7 @@ mod locations {
     }
     pub trait AsInLocation: Serialize + for<'de> Deserialize<'de> {
         fn from_enum(loc: &InLocation) -> Option<Self>;
+        #[allow(dead_code)]
         fn to_enum(&self) -> InLocation;
     }
 
@@ -546,21 +598,22 @@ pub struct Response {
     pub schema: Option<Type>,
 }
 
-#[derive(Debug)]
+/// An `in: body` parameter, whose type is given by its `schema`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BodyParameter {
+    pub name: String,
+    #[serde(default)]
+    pub required: bool,
+    pub schema: Type,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Default)]
 pub struct ParameterLocations {
     pub query: Vec<Parameter<locations::Query>>,
     pub header: Vec<Parameter<locations::Header>>,
     pub path: Vec<Parameter<locations::Path>>,
-}
-
-impl Default for ParameterLocations {
-    fn default() -> Self {
-        ParameterLocations {
-            query: Vec::new(),
-            header: Vec::new(),
-            path: Vec::new(),
-        }
-    }
+    pub body: Option<BodyParameter>,
 }
 
 impl<'de> Deserialize<'de> for ParameterLocations {
@@ -568,68 +621,69 @@ impl<'de> Deserialize<'de> for ParameterLocations {
     where
         D: serde::Deserializer<'de>,
     {
-        let params: Vec<Parameter<InLocation>> = Deserialize::deserialize(deserializer)?;
+        let raw: Vec<serde_json::Value> = Deserialize::deserialize(deserializer)?;
+        let mut params: Vec<Parameter<InLocation>> = Vec::new();
+        let mut body = None;
+        for param in raw {
+            match param.get("in").and_then(|location| location.as_str()) {
+                Some("body") => {
+                    body = Some(serde_json::from_value(param).map_err(serde::de::Error::custom)?)
+                }
+                // Form data isn't sent by the generated client.
+                Some("formData") => {}
+                _ => params.push(serde_json::from_value(param).map_err(serde::de::Error::custom)?),
+            }
+        }
         let query = params
             .iter()
             .filter_map(|x| {
-                if let Some(q) = locations::Query::from_enum(&x.in_) {
-                    Some(Parameter {
-                        name: x.name.clone(),
-                        in_: q,
-                        required: x.required,
-                        r#type: Type {
-                            description: x.r#type.description.clone(),
-                            schema_object: x.r#type.schema_object.clone(),
-                        },
-                        description: x.description.clone(),
-                    })
-                } else {
-                    None
-                }
+                locations::Query::from_enum(&x.in_).map(|q| Parameter {
+                    name: x.name.clone(),
+                    in_: q,
+                    required: x.required,
+                    r#type: Type {
+                        description: x.r#type.description.clone(),
+                        schema_object: x.r#type.schema_object.clone(),
+                    },
+                    description: x.description.clone(),
+                })
             })
             .collect::<Vec<_>>();
         let header = params
             .iter()
             .filter_map(|x| {
-                if let Some(h) = locations::Header::from_enum(&x.in_) {
-                    Some(Parameter {
-                        name: x.name.clone(),
-                        in_: h,
-                        required: x.required,
-                        r#type: Type {
-                            description: x.r#type.description.clone(),
-                            schema_object: x.r#type.schema_object.clone(),
-                        },
-                        description: x.description.clone(),
-                    })
-                } else {
-                    None
-                }
+                locations::Header::from_enum(&x.in_).map(|h| Parameter {
+                    name: x.name.clone(),
+                    in_: h,
+                    required: x.required,
+                    r#type: Type {
+                        description: x.r#type.description.clone(),
+                        schema_object: x.r#type.schema_object.clone(),
+                    },
+                    description: x.description.clone(),
+                })
             })
             .collect::<Vec<_>>();
         let path = params
             .iter()
             .filter_map(|x| {
-                if let Some(p) = locations::Path::from_enum(&x.in_) {
-                    Some(Parameter {
-                        name: x.name.clone(),
-                        in_: p,
-                        required: x.required,
-                        r#type: Type {
-                            description: x.r#type.description.clone(),
-                            schema_object: x.r#type.schema_object.clone(),
-                        },
-                        description: x.description.clone(),
-                    })
-                } else {
-                    None
-                }
+                locations::Path::from_enum(&x.in_).map(|p| Parameter {
+                    name: x.name.clone(),
+                    in_: p,
+                    required: x.required,
+                    r#type: Type {
+                        description: x.r#type.description.clone(),
+                        schema_object: x.r#type.schema_object.clone(),
+                    },
+                    description: x.description.clone(),
+                })
             })
             .collect::<Vec<_>>();
         Ok(ParameterLocations {
             query,
             header,
             path,
+            body,
         })
     }
 }
@@ -649,6 +703,11 @@ impl Serialize for ParameterLocations {
         for param in &self.path {
             params.push(serde_json::to_value(param).map_err(serde::ser::Error::custom)?);
         }
+        if let Some(param) = &self.body {
+            let mut param = serde_json::to_value(param).map_err(serde::ser::Error::custom)?;
+            param["in"] = "body".into();
+            params.push(param);
+        }
         params.serialize(serializer)
     }
 }
@@ -670,6 +729,7 @@ pub struct Operation {
 pub enum SwaggerVersion {
     #[serde(rename = "2.0")]
     V2,
+    /// OpenAPI 3.x, rewritten into the 2.0 layout by [`normalize`](crate::openapi::normalize).
     #[serde(rename = "3.0")]
     V3,
 }
@@ -750,6 +810,7 @@ pub struct Type {
 }
 
 #[derive(Deserialize, Debug)]
+#[allow(dead_code)]
 pub struct SwaggerFile {
     pub swagger: SwaggerVersion,
     pub info: Info,
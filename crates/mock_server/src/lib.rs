@@ -0,0 +1,169 @@
+//! A local imitation of the PTV timetable API.
+//!
+//! Every `GET` path in the bundled `v3` swagger file is served. Requests must be signed
+//! exactly as [`ptv::Client`] signs them; responses come from fixture files (in the
+//! [`ptv::Cassette`] format) when one exists for the request, and are otherwise generated
+//! from the operation's response schema. Latency, error statuses and malformed payloads
+//! can be injected with [`Faults`].
+//!
+//! ```no_run
+//! # async fn run() -> std::io::Result<()> {
+//! let addr = mock_server::spawn(mock_server::Config::default()).await?;
+//! let client = ptv::Client::builder(mock_server::DEFAULT_DEVID, mock_server::DEFAULT_KEY)
+//!     .base_url(format!("http://{addr}"))
+//!     .build()
+//!     .unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+use axum::{
+    Router,
+    http::{StatusCode, Uri, header},
+    response::{IntoResponse, Response},
+};
+use ptv::{Cassette, Transport, signing};
+use serde_json::json;
+use tokio::net::TcpListener;
+
+pub mod spec;
+pub use ptv::spec::{Spec, V3_SPEC};
+
+pub const DEFAULT_DEVID: &str = "1000000";
+pub const DEFAULT_KEY: &str = "00000000-0000-0000-0000-000000000000";
+
+/// Errors, latency and broken payloads to inject into responses.
+#[derive(Debug, Clone)]
+pub struct Faults {
+    /// Delay added before every response.
+    pub latency: Duration,
+    /// Fraction of requests (`0.0..=1.0`) answered with `error_status`.
+    pub error_rate: f64,
+    pub error_status: StatusCode,
+    /// Fraction of successful responses whose body is cut short, so it is no longer valid JSON.
+    pub malformed_rate: f64,
+}
+
+impl Default for Faults {
+    fn default() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            error_rate: 0.0,
+            error_status: StatusCode::SERVICE_UNAVAILABLE,
+            malformed_rate: 0.0,
+        }
+    }
+}
+
+pub struct Config {
+    pub devid: String,
+    pub key: String,
+    pub spec: Spec,
+    /// Directory of recorded fixtures, checked before falling back to generated samples.
+    pub fixtures: Option<PathBuf>,
+    pub faults: Faults,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            devid: DEFAULT_DEVID.to_string(),
+            key: DEFAULT_KEY.to_string(),
+            spec: Spec::v3(),
+            fixtures: None,
+            faults: Faults::default(),
+        }
+    }
+}
+
+struct State {
+    config: Config,
+    fixtures: Option<Cassette>,
+}
+
+/// A router answering every request from `config`.
+pub fn router(config: Config) -> Router {
+    let state = Arc::new(State {
+        fixtures: config.fixtures.clone().map(Cassette::replay),
+        config,
+    });
+    Router::new().fallback(move |uri: Uri| {
+        let state = state.clone();
+        async move { handle(&state, uri).await }
+    })
+}
+
+/// Serve `config` on `listener` until the process exits.
+pub async fn serve(listener: TcpListener, config: Config) -> std::io::Result<()> {
+    axum::serve(listener, router(config)).await
+}
+
+/// Serve `config` on an ephemeral localhost port in the background, returning its address.
+pub async fn spawn(config: Config) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(serve(listener, config));
+    Ok(addr)
+}
+
+async fn handle(state: &State, uri: Uri) -> Response {
+    let faults = &state.config.faults;
+    if !faults.latency.is_zero() {
+        tokio::time::sleep(faults.latency).await;
+    }
+
+    let path_and_query = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+    if let Err(message) = verify(&state.config.devid, &state.config.key, path_and_query) {
+        return error(StatusCode::FORBIDDEN, message);
+    }
+    if fastrand::f64() < faults.error_rate {
+        return error(faults.error_status, "Injected failure");
+    }
+
+    let (status, body) = match respond(state, uri.path(), path_and_query).await {
+        Some(response) => response,
+        None => return error(StatusCode::NOT_FOUND, "Resource not found"),
+    };
+    let mut body = body;
+    if status.is_success() && fastrand::f64() < faults.malformed_rate {
+        body.truncate(body.floor_char_boundary(body.len() / 2));
+    }
+    (status, [(header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+/// The fixture recorded for this request, or a sample generated from the spec.
+async fn respond(state: &State, path: &str, path_and_query: &str) -> Option<(StatusCode, String)> {
+    if let Some(fixtures) = &state.fixtures
+        && let Ok(res) = fixtures.send(path_and_query).await
+    {
+        return Some((res.status, res.body));
+    }
+    let template = state.config.spec.find(path)?;
+    let sample = spec::sample(&state.config.spec, template)?;
+    Some((StatusCode::OK, sample.to_string()))
+}
+
+/// Check `devid` and `signature` the same way the real API does.
+fn verify(devid: &str, key: &str, path_and_query: &str) -> Result<(), &'static str> {
+    if !path_and_query.contains("&signature=") {
+        return Err("Forbidden (signature missing)");
+    }
+    if signing::devid(path_and_query) != Some(devid) {
+        return Err("Forbidden (invalid devid)");
+    }
+    if !signing::verify(key, path_and_query) {
+        return Err("Forbidden (invalid signature)");
+    }
+    Ok(())
+}
+
+/// A response shaped like the API's own `ApiError` payload.
+fn error(status: StatusCode, message: &str) -> Response {
+    let body = json!({
+        "message": message,
+        "status": { "version": "3.0", "health": 1 },
+    });
+    (status, axum::Json(body)).into_response()
+}
@@ -0,0 +1,61 @@
+use std::{path::PathBuf, time::Duration};
+
+use axum::http::StatusCode;
+use mock_server::{Config, DEFAULT_DEVID, DEFAULT_KEY, Faults, Spec};
+use tokio::net::TcpListener;
+
+const USAGE: &str = "Usage: ptv-mock-server [options]
+
+Options:
+  --addr <addr>             Address to listen on (default 127.0.0.1:8080)
+  --spec <file>             Swagger file to serve (default: the bundled v3 spec)
+  --devid <id>              Developer ID to accept (default: $DEVID, or a fixed test ID)
+  --key <key>               Key requests must be signed with (default: $KEY, or a fixed test key)
+  --fixtures <dir>          Directory of recorded fixtures to serve before generated samples
+  --latency-ms <ms>         Delay added before every response
+  --error-rate <0..1>       Fraction of requests answered with --error-status
+  --error-status <code>     Status used for injected errors (default 503)
+  --malformed-rate <0..1>   Fraction of responses with a truncated body";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut addr = "127.0.0.1:8080".to_string();
+    let mut config = Config {
+        devid: std::env::var("DEVID").unwrap_or_else(|_| DEFAULT_DEVID.to_string()),
+        key: std::env::var("KEY").unwrap_or_else(|_| DEFAULT_KEY.to_string()),
+        ..Config::default()
+    };
+    let mut faults = Faults::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {arg}\n\n{USAGE}"))?;
+        match arg.as_str() {
+            "--addr" => addr = value,
+            "--spec" => config.spec = Spec::parse(&std::fs::read_to_string(value)?)?,
+            "--devid" => config.devid = value,
+            "--key" => config.key = value,
+            "--fixtures" => config.fixtures = Some(PathBuf::from(value)),
+            "--latency-ms" => faults.latency = Duration::from_millis(value.parse()?),
+            "--error-rate" => faults.error_rate = value.parse()?,
+            "--error-status" => faults.error_status = StatusCode::from_u16(value.parse()?)?,
+            "--malformed-rate" => faults.malformed_rate = value.parse()?,
+            _ => return Err(format!("Unknown option {arg}\n\n{USAGE}").into()),
+        }
+    }
+    config.faults = faults;
+
+    let listener = TcpListener::bind(&addr).await?;
+    println!(
+        "Mock PTV API listening on http://{}",
+        listener.local_addr()?
+    );
+    mock_server::serve(listener, config).await?;
+    Ok(())
+}
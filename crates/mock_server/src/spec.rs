@@ -0,0 +1,47 @@
+//! Sample responses derived from a [`Spec`].
+
+use ptv::spec::Spec;
+use serde_json::{Map, Value, json};
+
+/// How deep `$ref`s are followed when generating samples, so recursive definitions terminate.
+const MAX_DEPTH: usize = 12;
+
+/// A sample `200` response body for the `GET` operation at `template`.
+pub fn sample(spec: &Spec, template: &str) -> Option<Value> {
+    let schema = spec.response_schema(template)?;
+    Some(sample_schema(spec, schema, 0))
+}
+
+fn sample_schema(spec: &Spec, schema: &Value, depth: usize) -> Value {
+    if depth > MAX_DEPTH {
+        return Value::Null;
+    }
+    let schema = spec.resolve(schema);
+    if let Some(first) = schema["enum"].as_array().and_then(|e| e.first()) {
+        return first.clone();
+    }
+    match schema["type"].as_str() {
+        Some("object") | None if schema.get("properties").is_some() => {
+            let properties = schema["properties"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(name, property)| (name.clone(), sample_schema(spec, property, depth + 1)))
+                .collect::<Map<_, _>>();
+            Value::Object(properties)
+        }
+        Some("object") => json!({}),
+        Some("array") => json!([sample_schema(spec, &schema["items"], depth + 1)]),
+        Some("integer") => json!(0),
+        Some("number") => json!(0.0),
+        Some("boolean") => json!(false),
+        Some("string") => match schema["format"].as_str() {
+            Some("date-time") => {
+                json!(chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string())
+            }
+            Some("date") => json!(chrono::Utc::now().format("%Y-%m-%d").to_string()),
+            _ => json!("string"),
+        },
+        _ => Value::Null,
+    }
+}
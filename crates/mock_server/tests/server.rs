@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use mock_server::{Config, DEFAULT_DEVID, DEFAULT_KEY, Faults, spawn};
+use ptv::{core::generated_types::*, *};
+
+async fn client(config: Config, key: &str) -> Client {
+    let addr = spawn(config).await.unwrap();
+    Client::builder(DEFAULT_DEVID, key)
+        .base_url(format!("http://{addr}"))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn serves_generated_samples() {
+    let client = client(Config::default(), DEFAULT_KEY).await;
+
+    let res = client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert_eq!(res.directions.unwrap().len(), 1);
+
+    let res = client
+        .get_stops_by_distance_by_latitude_and_longitude(
+            -37.81,
+            144.962,
+            GetStopsByDistanceByLatitudeAndLongitudeParams {
+                max_results: Some(20),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(res.stops.is_some());
+}
+
+#[tokio::test]
+async fn rejects_bad_signatures() {
+    let client = client(Config::default(), "not-the-key").await;
+
+    let err = client
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(http::StatusCode::FORBIDDEN));
+    assert!(err.to_string().contains("invalid signature"));
+}
+
+#[tokio::test]
+async fn serves_fixtures_before_samples() {
+    let dir = std::env::temp_dir().join(format!("ptv-mock-fixtures-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let fixture = Fixture {
+        method: "GET".into(),
+        path: "/v3/directions/route/1".into(),
+        status: 200,
+        headers: Default::default(),
+        body: r#"{ "directions": [ { "direction_id": 7, "direction_name": "Recorded" } ] }"#.into(),
+    };
+    std::fs::write(
+        Cassette::replay(&dir).fixture_file(&fixture.path),
+        serde_json::to_vec(&fixture).unwrap(),
+    )
+    .unwrap();
+
+    let client = client(
+        Config {
+            fixtures: Some(dir.clone()),
+            ..Config::default()
+        },
+        DEFAULT_KEY,
+    )
+    .await;
+    let res = client.get_directions_by_route_id(RouteId(1)).await.unwrap();
+    assert_eq!(
+        res.directions.unwrap()[0].direction_name.as_deref(),
+        Some("Recorded")
+    );
+    // Anything not recorded still gets a generated sample.
+    client.get_directions_by_route_id(RouteId(2)).await.unwrap();
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test]
+async fn injects_faults() {
+    let failing = client(
+        Config {
+            faults: Faults {
+                error_rate: 1.0,
+                ..Faults::default()
+            },
+            ..Config::default()
+        },
+        DEFAULT_KEY,
+    )
+    .await;
+    let err = failing
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(http::StatusCode::SERVICE_UNAVAILABLE));
+
+    let malformed = client(
+        Config {
+            faults: Faults {
+                malformed_rate: 1.0,
+                latency: Duration::from_millis(20),
+                ..Faults::default()
+            },
+            ..Config::default()
+        },
+        DEFAULT_KEY,
+    )
+    .await;
+    let err = malformed
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Deserialize { .. }));
+}
+
+#[tokio::test]
+async fn truncates_multi_byte_fixtures() {
+    let dir = std::env::temp_dir().join(format!("ptv-mock-utf8-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let fixture = Fixture {
+        method: "GET".into(),
+        path: "/v3/directions/route/1".into(),
+        status: 200,
+        headers: Default::default(),
+        body: r#"{ "directions": [ { "direction_name": "––––––––––––––––––––––––" } ] }"#.into(),
+    };
+    std::fs::write(
+        Cassette::replay(&dir).fixture_file(&fixture.path),
+        serde_json::to_vec(&fixture).unwrap(),
+    )
+    .unwrap();
+
+    let client = client(
+        Config {
+            fixtures: Some(dir.clone()),
+            faults: Faults {
+                malformed_rate: 1.0,
+                ..Faults::default()
+            },
+            ..Config::default()
+        },
+        DEFAULT_KEY,
+    )
+    .await;
+    let err = client
+        .get_directions_by_route_id(RouteId(1))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Deserialize { .. }), "{err:?}");
+
+    std::fs::remove_dir_all(dir).ok();
+}
@@ -276,7 +276,9 @@ fn create_statement_vec(
                     };
 
 
+                    // The `..Default::default()` tail is redundant when every field is given.
                     let wow: Stmt = parse_quote_spanned! { span =>
+                        #[allow(clippy::needless_update)]
                         #map.insert(#test_name, Arc::new(|| {
                             Box::pin(async {
                                 let res = (CLIENT.#name(#params)).await?;